@@ -1,32 +1,52 @@
-use clap::Parser;
+mod profile;
+mod scheduler;
+
+use crate::profile::ProfileConfig;
+use crate::scheduler::{Scheduler, SchedulerConfig};
+use clap::{CommandFactory, FromArgMatches, Parser};
 use crabe_decision::pipeline::{DecisionConfig, DecisionPipeline};
+use crabe_decision::{manager, strategy};
 use crabe_filter::{FilterConfig, FilterPipeline};
 use crabe_framework::component::{
     Component, DecisionComponent, FilterComponent, GuardComponent, InputComponent, OutputComponent,
     ToolComponent,
 };
 use crabe_framework::config::CommonConfig;
-use crabe_framework::data::output::FeedbackMap;
-use crabe_framework::data::tool::ToolCommands;
+use crabe_framework::data::output::{CommandMap, FeedbackMap};
+use crabe_framework::data::plot::Plotter;
+use crabe_framework::data::timing::{LoopTimings, StepTimings};
+use crabe_framework::data::tool::{ToolCommand, ToolData};
 use crabe_framework::data::world::World;
-use crabe_guard::pipeline::{GuardConfig, GuardPipeline};
+use crabe_guard::pipeline::{self as guard, GuardConfig, GuardPipeline};
+use crabe_io::league::simulator::synchronous::vision_channel;
 use crabe_io::pipeline::input::{InputConfig, InputPipeline};
 use crabe_io::pipeline::output::{OutputConfig, OutputPipeline};
+use crabe_io::recorder::Recorder;
 use crabe_io::tool::ToolConfig;
-use crabe_io::tool::ToolServer;
+use crabe_io::tool::{ComponentNames, ToolServer};
 use env_logger::Env;
+use log::{info, warn};
+use std::ffi::OsString;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
+#[command(args_override_self = true)]
 pub struct Cli {
+    #[command(flatten)]
+    #[command(next_help_heading = "Profile")]
+    pub profile_config: ProfileConfig,
+
     #[command(flatten)]
     #[command(next_help_heading = "Common")]
     pub common: CommonConfig,
 
+    #[command(flatten)]
+    #[command(next_help_heading = "Scheduler")]
+    pub scheduler_config: SchedulerConfig,
+
     #[command(flatten)]
     #[command(next_help_heading = "Input")]
     pub input_config: InputConfig,
@@ -61,6 +81,8 @@ pub struct SystemBuilder {
     guard_component: Option<Box<dyn GuardComponent>>,
     output_component: Option<Box<dyn OutputComponent>>,
     world: Option<World>,
+    scheduler: Option<Scheduler>,
+    plotter: Option<Plotter>,
 }
 
 impl SystemBuilder {
@@ -99,6 +121,16 @@ impl SystemBuilder {
         self
     }
 
+    fn scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    fn plotter(mut self, plotter: Plotter) -> Self {
+        self.plotter = Some(plotter);
+        self
+    }
+
     fn build(self) -> System {
         let running = Arc::new(AtomicBool::new(true));
         let running_ctrlc = Arc::clone(&running);
@@ -116,6 +148,10 @@ impl SystemBuilder {
             output_component: self.output_component.expect("missing output component"),
             running,
             world: self.world.expect("missing world"),
+            scheduler: self.scheduler.expect("missing scheduler"),
+            paused: false,
+            tool_data: ToolData::default(),
+            plotter: self.plotter.expect("missing plotter"),
         }
     }
 }
@@ -129,23 +165,97 @@ pub struct System {
     output_component: Box<dyn OutputComponent>,
     running: Arc<AtomicBool>,
     world: World,
+    scheduler: Scheduler,
+    /// Whether the decision component is paused by the tools.
+    paused: bool,
+    /// The data sent to the tools, kept from one iteration to another.
+    tool_data: ToolData,
+    /// The plotter of the filters and guards, whose samples are moved into the tool data.
+    plotter: Plotter,
+}
+
+/// Runs `f` and returns its result along with the time it took.
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
 }
 
 impl System {
-    // TODO: Use refresh rate
-    pub fn run(&mut self, _refresh_rate: Duration) {
+    pub fn run(&mut self) {
         let mut feedback: FeedbackMap = Default::default();
+        let mut timings = LoopTimings {
+            period: self.scheduler.period(),
+            ..Default::default()
+        };
 
+        self.scheduler.reset();
         while self.running.load(Ordering::SeqCst) {
-            let receive_data = self.input_component.step(&mut feedback);
-            self.filter_component.step(receive_data, &mut self.world);
-            let (mut command_map, mut tool_data) = self.decision_component.step(&self.world);
-            self.tool_component
-                .step(&self.world, &mut tool_data, &mut command_map);
-            self.guard_component
-                .step(&self.world, &mut command_map, &mut ToolCommands);
-            feedback = self.output_component.step(command_map, ToolCommands);
-            thread::sleep(_refresh_rate);
+            let (receive_data, input_time) = timed(|| self.input_component.step(&mut feedback));
+            let (_, filter_time) =
+                timed(|| self.filter_component.step(receive_data, &mut self.world));
+            let (mut command_map, decision_time) = timed(|| {
+                if self.paused {
+                    // The robots stop, unless the tools command them.
+                    let stop: CommandMap = self
+                        .world
+                        .allies_bot
+                        .keys()
+                        .map(|id| (*id, Default::default()))
+                        .collect();
+                    stop
+                } else {
+                    self.decision_component
+                        .step(&self.world, &mut self.tool_data)
+                }
+            });
+
+            // The tools receive the timings of the last complete iteration.
+            self.tool_data.timings = timings.clone();
+            self.tool_data.plots.extend(self.plotter.take());
+            let (mut tool_commands, tool_time) = timed(|| {
+                self.tool_component
+                    .step(&self.world, &mut self.tool_data, &mut command_map)
+            });
+            // The samples are only sent once.
+            self.tool_data.plots.clear();
+            for command in &tool_commands {
+                match command {
+                    ToolCommand::Pause if !self.paused => info!("AI paused by a tool"),
+                    ToolCommand::Resume if self.paused => info!("AI resumed by a tool"),
+                    _ => continue,
+                }
+                self.paused = matches!(command, ToolCommand::Pause);
+            }
+            self.decision_component.apply_tool_commands(&tool_commands);
+            let (_, guard_time) = timed(|| {
+                self.guard_component
+                    .step(&self.world, &mut command_map, &mut tool_commands)
+            });
+            let (output_feedback, output_time) =
+                timed(|| self.output_component.step(command_map, tool_commands));
+            feedback = output_feedback;
+
+            timings.steps = StepTimings {
+                input: input_time,
+                filter: filter_time,
+                decision: decision_time,
+                tool: tool_time,
+                guard: guard_time,
+                output: output_time,
+            };
+            let tick = self.scheduler.wait();
+            timings.overrun = tick.overrun;
+            if tick.overrun {
+                timings.overrun_count += 1;
+                timings.skipped_ticks += tick.skipped as u64;
+                warn!(
+                    "System loop overrun: {:?} spent in the components for a period of {:?} ({:?})",
+                    timings.steps.total(),
+                    timings.period,
+                    timings.steps
+                );
+            }
         }
     }
 
@@ -159,26 +269,80 @@ impl System {
     }
 }
 
+/// Parses the command line on top of the profiles it selects, and returns the merged
+/// configuration along with its dump.
+fn parse_cli() -> (Cli, String) {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let mut command = profile::with_negated_flags(Cli::command());
+    let (cli_matches, matches) = profile::parse(&mut command, &args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    (cli, profile::dump(&command, &cli_matches, &matches))
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let (cli, config_dump) = parse_cli();
     let env = Env::default()
         .filter_or("CRABE_LOG_LEVEL", "info")
         .write_style_or("CRABE_LOG_STYLE", "always");
     env_logger::init_from_env(env);
+    info!("Effective configuration:\n{}", config_dump);
 
+    let scheduler = Scheduler::with_config(cli.scheduler_config);
+    let clock = scheduler.clock();
+    let plotter = Plotter::new(clock.clone());
+    // The synchronous simulator gives the vision to the output, which hands it to the input.
+    let (vision_tx, vision_rx) = vision_channel();
+    // The inbound packets, our commands and the feedback are recorded in the same log.
+    let recorder = Recorder::with_config(cli.input_config.record.as_deref(), &clock)
+        .expect("Failed to create the log file");
     let mut system = SystemBuilder::default()
         .world(World::with_config(&cli.common))
-        .input_component(InputPipeline::with_config(cli.input_config, &cli.common))
-        .filter_component(FilterPipeline::with_config(cli.filter_config, &cli.common))
+        .input_component(InputPipeline::with_config(
+            cli.input_config,
+            &cli.common,
+            &clock,
+            &vision_rx,
+            &recorder,
+        ))
+        .filter_component(FilterPipeline::with_config(
+            cli.filter_config,
+            &cli.common,
+            &clock,
+            &plotter,
+        ))
         .decision_component(DecisionPipeline::with_config(
             cli.decision_config,
             &cli.common,
+            &clock,
+        ))
+        .tool_component(ToolServer::with_config(
+            cli.tool_config,
+            &cli.common,
+            &clock,
+            ComponentNames {
+                managers: manager::registry().names(),
+                strategies: strategy::registry().names(),
+                guards: guard::registry().names(),
+            },
+        ))
+        .guard_component(GuardPipeline::with_config(
+            cli.guard_config,
+            &cli.common,
+            &clock,
+            &plotter,
+        ))
+        .output_component(OutputPipeline::with_config(
+            cli.output_config,
+            &cli.common,
+            &clock,
+            &vision_tx,
+            &recorder,
         ))
-        .tool_component(ToolServer::with_config(cli.tool_config, &cli.common))
-        .guard_component(GuardPipeline::with_config(cli.guard_config, &cli.common))
-        .output_component(OutputPipeline::with_config(cli.output_config, &cli.common))
+        .scheduler(scheduler)
+        .plotter(plotter)
         .build();
 
-    system.run(Duration::from_millis(16));
+    system.run();
     system.close();
 }
@@ -0,0 +1,328 @@
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::FromArgMatches;
+use clap::{Arg, ArgAction, ArgMatches, Args, Command};
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use toml::{Table, Value};
+
+/// The `ProfileConfig` struct holds the configuration profiles to load before the command line.
+///
+/// A profile is a TOML file whose keys are the long names of the command line arguments, in
+/// snake case or kebab case. The keys can be grouped in tables (e.g. `[input]`), the name of the
+/// tables is only there for readability.
+///
+/// A flag set by a profile is turned off on the command line with `--no-<flag>` (e.g. `--no-real`).
+#[derive(Args)]
+pub struct ProfileConfig {
+    /// Profiles to load, by name (looked up in the profile directory) or by path. When several
+    /// profiles are given, the last ones override the first ones, and the command line overrides
+    /// them all.
+    #[arg(long, value_delimiter = ',')]
+    pub profile: Vec<String>,
+    /// Directory in which the profiles are looked up by name.
+    #[arg(long, default_value = "profiles")]
+    pub profile_dir: PathBuf,
+}
+
+impl ProfileConfig {
+    /// Returns the path of the profile with the given name or path.
+    fn path(&self, profile: &str) -> PathBuf {
+        if profile.ends_with(".toml") || profile.contains(std::path::MAIN_SEPARATOR) {
+            PathBuf::from(profile)
+        } else {
+            self.profile_dir.join(format!("{}.toml", profile))
+        }
+    }
+
+    /// Loads the profiles and returns them as command line arguments, in order, leaving out the
+    /// arguments given on the command line `cli_matches`.
+    /// Exits with a usage error if a profile can't be loaded.
+    pub fn load(&self, command: &mut Command, cli_matches: &ArgMatches) -> Vec<OsString> {
+        let mut args = vec![];
+        for profile in &self.profile {
+            let path = self.path(profile);
+            let table = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| content.parse::<Table>().map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| {
+                    command
+                        .error(
+                            ErrorKind::Io,
+                            format!("cannot load profile {}: {}", path.display(), e),
+                        )
+                        .exit()
+                });
+            let profile_args = table_to_args(command, &table, cli_matches).unwrap_or_else(|e| {
+                command
+                    .error(
+                        ErrorKind::UnknownArgument,
+                        format!("invalid profile {}: {}", path.display(), e),
+                    )
+                    .exit()
+            });
+            args.extend(profile_args);
+        }
+        args
+    }
+}
+
+/// Adds a hidden `--no-<flag>` argument for each flag of the `command`, overriding the flag, so
+/// that the command line can turn off a flag set by a profile.
+pub fn with_negated_flags(mut command: Command) -> Command {
+    let flags: Vec<_> = command
+        .get_arguments()
+        .filter(|arg| matches!(arg.get_action(), ArgAction::SetTrue))
+        .filter_map(|arg| Some((arg.get_id().clone(), arg.get_long()?.to_string())))
+        .collect();
+    for (id, long) in flags {
+        command = command.arg(
+            Arg::new(format!("no_{}", id))
+                .long(format!("no-{}", long))
+                .action(ArgAction::SetTrue)
+                .overrides_with(id)
+                .hide(true),
+        );
+    }
+    command
+}
+
+/// Parses the command line `args` on top of the profiles it selects. Returns the arguments
+/// given on the command line only, and the arguments merged from the profiles and the command
+/// line.
+pub fn parse(command: &mut Command, args: &[OsString]) -> (ArgMatches, ArgMatches) {
+    let cli_matches = command.clone().get_matches_from(args);
+    let profile_config = ProfileConfig::from_arg_matches(&cli_matches).unwrap_or_else(|e| e.exit());
+
+    // The arguments given last override the previous ones, so the command line comes after
+    // the profiles. The lists are appended to instead, the profiles are left out for them.
+    let mut merged_args = args[..1].to_vec();
+    merged_args.extend(profile_config.load(command, &cli_matches));
+    merged_args.extend_from_slice(&args[1..]);
+    let matches = command.clone().get_matches_from(merged_args);
+    (cli_matches, matches)
+}
+
+/// Converts a profile into command line arguments, checking that its keys are arguments of the
+/// `command`. Flags set to `false` are left out since it is their default value, and so are the
+/// arguments given on the command line `cli_matches`, which replace the ones of the profile.
+fn table_to_args(
+    command: &Command,
+    table: &Table,
+    cli_matches: &ArgMatches,
+) -> Result<Vec<OsString>, String> {
+    let mut args = vec![];
+    for (key, value) in table {
+        if let Value::Table(section) = value {
+            args.extend(table_to_args(command, section, cli_matches)?);
+            continue;
+        }
+
+        let long = key.replace('_', "-");
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+            .filter(|arg| arg.get_id() != "profile")
+            .ok_or_else(|| format!("unknown argument `{}`", key))?;
+        if cli_matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let flag = format!("--{}", long);
+        match (value, arg.get_action()) {
+            (Value::Boolean(true), ArgAction::SetTrue) => args.push(flag.into()),
+            (Value::Boolean(false), ArgAction::SetTrue) => {}
+            (_, ArgAction::SetTrue) => return Err(format!("`{}` must be a boolean", key)),
+            (Value::Array(values), _) => {
+                for value in values {
+                    args.push(flag.clone().into());
+                    args.push(scalar(key, value)?.into());
+                }
+            }
+            (value, _) => {
+                args.push(flag.into());
+                args.push(scalar(key, value)?.into());
+            }
+        }
+    }
+    Ok(args)
+}
+
+fn scalar(key: &str, value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Integer(i) => Ok(i.to_string()),
+        Value::Float(f) => Ok(f.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        _ => Err(format!("`{}` must be a string, a number or a boolean", key)),
+    }
+}
+
+/// Formats a raw argument value as a TOML value.
+fn format_value(value: &str) -> String {
+    if value == "true" || value == "false" || value.parse::<f64>().is_ok() {
+        value.to_string()
+    } else {
+        Value::String(value.to_string()).to_string()
+    }
+}
+
+/// Dumps the effective configuration as a profile, with the origin of each value.
+///
+/// # Arguments
+///
+/// * `command`: The command line interface.
+/// * `cli_matches`: The arguments given on the command line only.
+/// * `matches`: The arguments merged from the profiles and the command line.
+pub fn dump(command: &Command, cli_matches: &ArgMatches, matches: &ArgMatches) -> String {
+    let mut dump = String::new();
+    let mut heading = None;
+    let arguments = command.get_arguments().filter(|arg| {
+        arg.get_long().is_some() && arg.get_help_heading() != Some("Profile") && !arg.is_hide_set()
+    });
+    for arg in arguments {
+        if arg.get_help_heading() != heading {
+            heading = arg.get_help_heading();
+            let section = heading.unwrap_or("cli").to_lowercase().replace(' ', "_");
+            let _ = writeln!(dump, "[{}]", section);
+        }
+
+        let id = arg.get_id().as_str();
+        let key = arg.get_long().unwrap_or(id).replace('-', "_");
+        let Some(values) = matches.get_raw(id) else {
+            let _ = writeln!(dump, "# {} is not set", key);
+            continue;
+        };
+        let values: Vec<_> = values.map(|v| format_value(&v.to_string_lossy())).collect();
+        let value = match values.as_slice() {
+            [value] => value.clone(),
+            values => format!("[{}]", values.join(", ")),
+        };
+        let origin = match (cli_matches.value_source(id), matches.value_source(id)) {
+            (Some(ValueSource::CommandLine), _) => "command line",
+            (_, Some(ValueSource::CommandLine)) => "profile",
+            (_, Some(ValueSource::EnvVariable)) => "environment",
+            _ => "default",
+        };
+        let _ = writeln!(dump, "{} = {} # {}", key, value, origin);
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn command() -> Command {
+        Command::new("test")
+            .arg(Arg::new("yellow").long("yellow").action(ArgAction::SetTrue))
+            .arg(Arg::new("real").long("real").action(ArgAction::SetTrue))
+            .arg(Arg::new("usb_port").long("usb-port"))
+            .arg(Arg::new("vision_port").long("vision-port"))
+    }
+
+    /// The matches of an empty command line.
+    fn no_matches() -> ArgMatches {
+        command().get_matches_from(["test"])
+    }
+
+    #[test]
+    fn profile_keys_become_arguments() {
+        let table: Table = r#"
+            [common]
+            yellow = true
+            real = false
+
+            [output]
+            usb_port = "/dev/ttyACM0"
+            vision-port = 10020
+        "#
+        .parse()
+        .unwrap();
+        let args = table_to_args(&command(), &table, &no_matches()).unwrap();
+        let expected: Vec<OsString> = [
+            "--yellow",
+            "--usb-port",
+            "/dev/ttyACM0",
+            "--vision-port",
+            "10020",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(args, expected);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let table: Table = "team = \"blue\"".parse().unwrap();
+        assert!(table_to_args(&command(), &table, &no_matches()).is_err());
+        let table: Table = "yellow = 1".parse().unwrap();
+        assert!(table_to_args(&command(), &table, &no_matches()).is_err());
+    }
+
+    #[test]
+    fn profile_flags_are_turned_off_on_the_command_line() {
+        let profile_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../profiles");
+        // The robot profile of the competition is relative to the root of the repository.
+        let mut command = with_negated_flags(crate::Cli::command())
+            .mut_arg("robot_profile", |arg| {
+                arg.value_parser(clap::value_parser!(String))
+            });
+        let args = |extra: &[&str]| -> Vec<OsString> {
+            [
+                "crabe",
+                "--profile",
+                "competition-real",
+                "--profile-dir",
+                profile_dir,
+            ]
+            .iter()
+            .chain(extra)
+            .map(OsString::from)
+            .collect()
+        };
+
+        let (_, matches) = parse(&mut command, &args(&[]));
+        assert!(matches.get_flag("real"));
+        assert!(matches.get_flag("gc"));
+
+        let (_, matches) = parse(&mut command, &args(&["--no-real", "--synchronous"]));
+        assert!(!matches.get_flag("real"));
+        assert!(matches.get_flag("synchronous"));
+        assert!(matches.get_flag("gc"));
+    }
+
+    #[test]
+    fn profile_lists_are_replaced_on_the_command_line() {
+        let command = command().arg(
+            Arg::new("guards")
+                .long("guards")
+                .action(ArgAction::Append)
+                .value_delimiter(','),
+        );
+        let table: Table = "guards = [\"speed\"]\nyellow = true".parse().unwrap();
+        let parse = |cli: &[&str]| -> Vec<String> {
+            let cli_matches = command.clone().get_matches_from(cli);
+            let mut args: Vec<OsString> = vec!["test".into()];
+            args.extend(table_to_args(&command, &table, &cli_matches).unwrap());
+            args.extend(cli[1..].iter().map(OsString::from));
+            let matches = command.clone().get_matches_from(args);
+            assert!(matches.get_flag("yellow"));
+            matches
+                .get_many::<String>("guards")
+                .unwrap()
+                .cloned()
+                .collect()
+        };
+
+        assert_eq!(parse(&["test"]), ["speed"]);
+        assert_eq!(parse(&["test", "--guards", "boundary"]), ["boundary"]);
+        assert_eq!(
+            parse(&["test", "--guards", "speed,boundary"]),
+            ["speed", "boundary"]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use clap::{Args, ValueEnum};
+use crabe_framework::clock::{ManualClock, SharedClock, WallClock};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The maximum number of late ticks that the `CatchUp` policy runs back to back.
+/// Beyond that (e.g. after the process was paused), the schedule is restarted from now.
+const MAX_CATCH_UP_TICKS: u32 = 5;
+
+/// The `OverrunPolicy` enum defines what the `Scheduler` does when an iteration ends after
+/// its deadline.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OverrunPolicy {
+    /// Keep the original deadlines and start the late iterations immediately until the loop
+    /// is back on schedule, so that the average rate is preserved.
+    CatchUp,
+    /// Drop the missed deadlines and wait for the next one, so that the loop stays aligned
+    /// on its period.
+    Skip,
+}
+
+/// The `SchedulerConfig` struct holds the configuration of the system loop scheduler.
+#[derive(Args)]
+pub struct SchedulerConfig {
+    /// Period of the system loop in milliseconds.
+    #[arg(long, default_value_t = 16)]
+    pub period_ms: u64,
+    /// What to do when an iteration of the system loop takes longer than its period.
+    #[arg(long, value_enum, default_value_t = OverrunPolicy::Skip)]
+    pub overrun_policy: OverrunPolicy,
+    /// Run on a manual clock moved forward by one period at each iteration, without waiting,
+    /// instead of the wall clock (for replays and headless simulations).
+    #[arg(long)]
+    pub manual_clock: bool,
+}
+
+/// The `Tick` struct describes how the iteration that just ended fitted in its period.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tick {
+    /// Whether the iteration ended after its deadline.
+    pub overrun: bool,
+    /// The number of ticks dropped to get back on schedule.
+    pub skipped: u32,
+}
+
+/// The `Scheduler` struct paces the system loop at a fixed period based on deadlines,
+/// so that the time spent in the components doesn't make the loop period drift.
+///
+/// With a manual clock, the scheduler doesn't wait but moves the clock forward by one period
+/// at each iteration, so that the system runs deterministically and as fast as possible.
+pub struct Scheduler {
+    period: Duration,
+    policy: OverrunPolicy,
+    next_deadline: Instant,
+    manual_clock: Option<Arc<ManualClock>>,
+}
+
+impl Scheduler {
+    /// Creates a new `Scheduler` with the given period and overrun policy.
+    pub fn new(period: Duration, policy: OverrunPolicy) -> Self {
+        Self {
+            period,
+            policy,
+            next_deadline: Instant::now() + period,
+            manual_clock: None,
+        }
+    }
+
+    /// Creates a new `Scheduler` that drives the given manual clock with the given period.
+    pub fn with_manual_clock(period: Duration, clock: Arc<ManualClock>) -> Self {
+        Self {
+            manual_clock: Some(clock),
+            ..Self::new(period, OverrunPolicy::Skip)
+        }
+    }
+
+    /// Creates a new `Scheduler` from the command line configuration.
+    pub fn with_config(config: SchedulerConfig) -> Self {
+        let period = Duration::from_millis(config.period_ms);
+        if config.manual_clock {
+            Self::with_manual_clock(period, Default::default())
+        } else {
+            Self::new(period, config.overrun_policy)
+        }
+    }
+
+    /// Returns the clock that the components must read the time from.
+    pub fn clock(&self) -> SharedClock {
+        match &self.manual_clock {
+            Some(clock) => clock.clone(),
+            None => Arc::new(WallClock),
+        }
+    }
+
+    /// Returns the period targeted by the scheduler.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Restarts the schedule, the current period ending one period from now.
+    pub fn reset(&mut self) {
+        self.next_deadline = Instant::now() + self.period;
+    }
+
+    /// Computes the next deadline and the state of the iteration that ended at `now`.
+    fn advance(&mut self, now: Instant) -> (Instant, Tick) {
+        let deadline = self.next_deadline;
+        if now <= deadline {
+            self.next_deadline = deadline + self.period;
+            return (
+                deadline,
+                Tick {
+                    overrun: false,
+                    skipped: 0,
+                },
+            );
+        }
+
+        let late = now - deadline;
+        let missed = (late.as_nanos() / self.period.as_nanos().max(1)) as u32;
+        let (wake_up, skipped) = match self.policy {
+            OverrunPolicy::CatchUp if missed < MAX_CATCH_UP_TICKS => (deadline, 0),
+            OverrunPolicy::CatchUp => (now, missed),
+            OverrunPolicy::Skip => (deadline + self.period * (missed + 1), missed + 1),
+        };
+        self.next_deadline = wake_up + self.period;
+
+        (
+            wake_up,
+            Tick {
+                overrun: true,
+                skipped,
+            },
+        )
+    }
+
+    /// Waits until the start of the next period and returns how the previous
+    /// iteration fitted in its own period.
+    pub fn wait(&mut self) -> Tick {
+        if let Some(clock) = &self.manual_clock {
+            clock.advance(self.period);
+            return Tick {
+                overrun: false,
+                skipped: 0,
+            };
+        }
+
+        let (wake_up, tick) = self.advance(Instant::now());
+        let now = Instant::now();
+        if wake_up > now {
+            thread::sleep(wake_up - now);
+        }
+        tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::clock::Clock;
+
+    const PERIOD: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn on_time_iterations_keep_the_period() {
+        let mut scheduler = Scheduler::new(PERIOD, OverrunPolicy::Skip);
+        let start = scheduler.next_deadline;
+        let (wake_up, tick) = scheduler.advance(start - Duration::from_millis(4));
+        assert_eq!(wake_up, start);
+        assert!(!tick.overrun);
+        assert_eq!(scheduler.next_deadline, start + PERIOD);
+    }
+
+    #[test]
+    fn catch_up_runs_late_ticks_immediately() {
+        let mut scheduler = Scheduler::new(PERIOD, OverrunPolicy::CatchUp);
+        let start = scheduler.next_deadline;
+        let now = start + Duration::from_millis(15);
+        let (wake_up, tick) = scheduler.advance(now);
+        assert!(wake_up <= now);
+        assert_eq!(tick.skipped, 0);
+        assert!(tick.overrun);
+        assert_eq!(scheduler.next_deadline, start + PERIOD);
+    }
+
+    #[test]
+    fn catch_up_restarts_after_a_long_stall() {
+        let mut scheduler = Scheduler::new(PERIOD, OverrunPolicy::CatchUp);
+        let now = scheduler.next_deadline + PERIOD * 20;
+        let (wake_up, tick) = scheduler.advance(now);
+        assert_eq!(wake_up, now);
+        assert_eq!(tick.skipped, 20);
+        assert_eq!(scheduler.next_deadline, now + PERIOD);
+    }
+
+    #[test]
+    fn skip_waits_for_the_next_slot() {
+        let mut scheduler = Scheduler::new(PERIOD, OverrunPolicy::Skip);
+        let start = scheduler.next_deadline;
+        let (wake_up, tick) = scheduler.advance(start + Duration::from_millis(15));
+        assert_eq!(wake_up, start + PERIOD * 2);
+        assert_eq!(tick.skipped, 2);
+        assert!(tick.overrun);
+    }
+
+    #[test]
+    fn manual_clock_advances_by_one_period() {
+        let clock = Arc::new(ManualClock::default());
+        let mut scheduler = Scheduler::with_manual_clock(PERIOD, clock.clone());
+        let start = scheduler.clock().now();
+        scheduler.wait();
+        scheduler.wait();
+        let elapsed = (clock.now() - start).to_std().unwrap();
+        assert_eq!(elapsed, PERIOD * 2);
+    }
+}
@@ -3,8 +3,11 @@ use crate::action::Action;
 use crabe_framework::data::output::{Command, Kick};
 use crabe_framework::data::tool::ToolData;
 use crabe_framework::data::world::{AllyInfo, Robot, World};
+use crabe_framework::parameter::{parameters, Parameter};
+use crabe_framework::robot_profile::MotionLimits;
 use nalgebra::{Isometry2, Point2, Vector2, Vector3};
 use std::f64::consts::PI;
+use std::sync::OnceLock;
 
 /// The `MoveTo` struct represents an action that moves the robot to a specific location on the field, with a given target orientation.
 #[derive(Clone)]
@@ -85,6 +88,76 @@ const GOTO_ROTATION: f64 = 1.5;
 /// The error tolerance for arriving at the target position.
 const ERR_TOLERANCE: f64 = 0.115;
 
+/// The gains and the velocity limits of the `MoveTo` action, which can be tuned at runtime.
+struct Gains {
+    speed: Parameter<f64>,
+    rotation: Parameter<f64>,
+    tolerance: Parameter<f64>,
+    max_linear: Parameter<f64>,
+    max_angular: Parameter<f64>,
+}
+
+static GAINS: OnceLock<Gains> = OnceLock::new();
+
+/// Returns the gains, bounded by the limits of our robots until the pipeline declares them.
+fn gains() -> &'static Gains {
+    GAINS.get_or_init(|| Gains::new(&MotionLimits::default()))
+}
+
+impl Gains {
+    /// Declares the gains, the velocity limits being those of `limits` by default. They can
+    /// only be lowered.
+    fn new(limits: &MotionLimits) -> Self {
+        let max_linear = limits.max_linear_velocity;
+        let max_angular = limits.max_angular_velocity;
+        Self {
+            speed: parameters().declare(
+                "move_to.speed",
+                "Factor speed to move towards the target position",
+                GOTO_SPEED,
+                0.0,
+                10.0,
+            ),
+            rotation: parameters().declare(
+                "move_to.rotation",
+                "Factor speed to rotate towards the target orientation",
+                GOTO_ROTATION,
+                0.0,
+                10.0,
+            ),
+            tolerance: parameters().declare(
+                "move_to.tolerance",
+                "Error tolerance for arriving at the target",
+                ERR_TOLERANCE,
+                0.0,
+                1.0,
+            ),
+            max_linear: parameters().declare(
+                "move_to.max_linear",
+                "Maximum linear velocity of the orders, in m.s-1",
+                max_linear,
+                0.0,
+                max_linear,
+            ),
+            max_angular: parameters().declare(
+                "move_to.max_angular",
+                "Maximum angular velocity of the orders, in rad.s-1",
+                max_angular,
+                0.0,
+                max_angular,
+            ),
+        }
+    }
+}
+
+impl MoveTo {
+    /// Declares the runtime parameters of the action, so that the tools can list them before
+    /// the first `MoveTo` runs. The orders are bounded by the velocity `limits` of our robots.
+    pub fn declare_parameters(limits: &MotionLimits) {
+        GAINS.get_or_init(|| Gains::new(limits));
+    }
+}
+
 impl Action for MoveTo {
     /// Returns the name of the action.
     fn name(&self) -> String {
@@ -104,7 +177,7 @@ impl Action for MoveTo {
     /// * `id`: The id of the robot for which the orders are computed.
     /// * `world`: The current state of the world.
     /// * `tools`: A collection of external tools used by the action, such as a viewer.
-    fn compute_order(&mut self, id: u8, world: &World, _tools: &mut ToolData) -> Command {
+    fn compute_order(&mut self, id: u8, world: &World, tools: &mut ToolData) -> Command {
         if let Some(robot) = world.allies_bot.get(&id) {
             let ti = frame_inv(robot_frame(robot));
             let target_in_robot = ti * Point2::new(self.target.x, self.target.y);
@@ -112,16 +185,25 @@ impl Action for MoveTo {
             let error_orientation = angle_wrap(self.orientation - robot.pose.orientation);
             let error_x = target_in_robot[0];
             let error_y = target_in_robot[1];
-            let arrived = Vector3::new(error_x, error_y, error_orientation).norm() < ERR_TOLERANCE;
+            let gains = gains();
+            let arrived =
+                Vector3::new(error_x, error_y, error_orientation).norm() < gains.tolerance.get();
             if arrived {
                 self.state = State::Done;
             }
 
+            // The linear order keeps its direction when it is bounded.
+            let linear = (gains.speed.get() * Vector2::new(error_x, error_y))
+                .cap_magnitude(gains.max_linear.get());
+            let max_angular = gains.max_angular.get();
             let order = Vector3::new(
-                GOTO_SPEED * error_x,
-                GOTO_SPEED * error_y,
-                GOTO_ROTATION * error_orientation,
+                linear.x,
+                linear.y,
+                (gains.rotation.get() * error_orientation).clamp(-max_angular, max_angular),
             );
+            tools.plot(format!("robot{}/vx_cmd", id), order.x);
+            tools.plot(format!("robot{}/vy_cmd", id), order.y);
+            tools.plot(format!("robot{}/w_cmd", id), order.z);
 
             Command {
                 forward_velocity: order.x as f32,
@@ -136,3 +218,39 @@ impl Action for MoveTo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::config::CommonConfig;
+    use crabe_framework::data::world::Pose;
+
+    #[test]
+    fn orders_are_bounded_by_the_velocity_limits() {
+        let mut world = World::with_config(&CommonConfig {
+            yellow: false,
+            real: false,
+            synchronous: false,
+            robot_profile: None,
+        });
+        world.allies_bot.insert(
+            0,
+            Robot {
+                pose: Pose::new(Point2::new(-3.0, -4.0), 0.0),
+                ..Default::default()
+            },
+        );
+        let mut move_to = MoveTo::new(Point2::new(3.0, 4.0), -3.0, 0.0, false, None);
+
+        let command = move_to.compute_order(0, &world, &mut ToolData::default());
+
+        let limits = MotionLimits::default();
+        let linear = Vector2::new(command.forward_velocity, command.left_velocity);
+        assert!((linear.norm() as f64 - limits.max_linear_velocity).abs() < 1e-6);
+        assert!((linear.y / linear.x - 4.0 / 3.0).abs() < 1e-6);
+        assert_eq!(
+            command.angular_velocity,
+            -limits.max_angular_velocity as f32
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use crate::action::ActionWrapper;
+use crate::manager::manual::Manual;
+use crate::strategy::Strategy;
 use crabe_framework::data::tool::ToolData;
 use crabe_framework::data::world::World;
+use crabe_framework::registry::Registry;
 
 pub mod manual;
 
@@ -18,3 +21,10 @@ pub trait Manager {
         action_wrapper: &mut ActionWrapper,
     );
 }
+
+/// Returns the registry of the managers, which are built with the strategies chosen from the
+/// command line.
+pub fn registry() -> Registry<dyn Manager, Vec<Box<dyn Strategy>>> {
+    Registry::<dyn Manager, Vec<Box<dyn Strategy>>>::new()
+        .register("manual", |strategies| Box::new(Manual::new(strategies)))
+}
@@ -1,6 +1,5 @@
 use crate::action::ActionWrapper;
 use crate::manager::Manager;
-use crate::strategy::testing::Square;
 use crate::strategy::Strategy;
 use crabe_framework::data::tool::ToolData;
 use crabe_framework::data::world::World;
@@ -9,8 +8,8 @@ use crabe_framework::data::world::World;
 /// added to its list.
 /// It's used for testing individual strategies only and not meant to be used during an actual game.
 ///
-/// To add a strategy, simply create a new instance of the desired strategy and add it to the
-/// `strategies` field in the `new()` method of the `Manual` struct.
+/// The strategies to test are chosen from the command line with `--strategies`, a new
+/// strategy must be added to the strategy registry to be available.
 #[derive(Default)]
 pub struct Manual {
     strategies: Vec<Box<dyn Strategy>>,
@@ -18,10 +17,8 @@ pub struct Manual {
 
 impl Manual {
     /// Creates a new `Manual` instance with the desired strategies to test.
-    pub fn new() -> Self {
-        Self {
-            strategies: vec![Box::new(Square::new(0))],
-        }
+    pub fn new(strategies: Vec<Box<dyn Strategy>>) -> Self {
+        Self { strategies }
     }
 }
 
@@ -1,16 +1,31 @@
+use crate::action::move_to::MoveTo;
 use crate::action::ActionWrapper;
-use crate::manager::manual::Manual;
-use crate::manager::Manager;
+use crate::manager::{self, Manager};
+use crate::strategy::StrategyChoice;
+use clap::builder::PossibleValuesParser;
 use clap::Args;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::component::{Component, DecisionComponent};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::data::output::CommandMap;
-use crabe_framework::data::tool::ToolData;
+use crabe_framework::data::tool::{ToolCommand, ToolCommands, ToolData};
 use crabe_framework::data::world::World;
+use log::{info, warn};
 
 /// The `DecisionConfig` struct is used to hold configuration options for the decision pipeline.
 #[derive(Args)]
-pub struct DecisionConfig {}
+pub struct DecisionConfig {
+    /// Manager coordinating the strategies.
+    #[arg(
+        long,
+        default_value = "manual",
+        value_parser = PossibleValuesParser::new(manager::registry().names())
+    )]
+    pub manager: String,
+    /// Strategies given to the manager, as `name` or `name:robot_id` (robot 0 by default).
+    #[arg(long, value_delimiter = ',', default_value = "square")]
+    pub strategies: Vec<StrategyChoice>,
+}
 
 /// The `DecisionPipeline` struct represents the decision-making pipeline used by the robot.
 /// It consists of an action wrapper and a manager, both of which can be customized
@@ -20,16 +35,87 @@ pub struct DecisionConfig {}
 pub struct DecisionPipeline {
     action_wrapper: ActionWrapper,
     manager: Box<dyn Manager>,
+    manager_name: String,
+    /// The strategies given to the manager.
+    strategies: Vec<StrategyChoice>,
+    clock: SharedClock,
+    /// Whether the annotations of the previous manager must be removed at the next step.
+    clear_annotations: bool,
+}
+
+/// Parses the strategies sent by a tool, the invalid ones are logged and left out.
+fn parse_strategies(strategies: &[String]) -> Vec<StrategyChoice> {
+    strategies
+        .iter()
+        .filter_map(|s| {
+            s.parse()
+                .map_err(|e| warn!("Ignoring strategy from a tool: {}", e))
+                .ok()
+        })
+        .collect()
 }
 
 impl DecisionPipeline {
     /// Creates a new `DecisionPipeline` instance with the given configuration and common configuration options.
-    pub fn with_config(_decision_cfg: DecisionConfig, _common_cfg: &CommonConfig) -> Self {
+    pub fn with_config(
+        decision_cfg: DecisionConfig,
+        common_cfg: &CommonConfig,
+        clock: &SharedClock,
+    ) -> Self {
+        MoveTo::declare_parameters(&common_cfg.robot_profile().limits);
         Self {
             action_wrapper: ActionWrapper::default(),
-            manager: Box::new(Manual::new()),
+            manager: manager::registry()
+                .create(
+                    &decision_cfg.manager,
+                    decision_cfg
+                        .strategies
+                        .iter()
+                        .map(StrategyChoice::create)
+                        .collect(),
+                )
+                .expect("manager names are checked when parsed"),
+            manager_name: decision_cfg.manager,
+            strategies: decision_cfg.strategies,
+            clock: clock.clone(),
+            clear_annotations: false,
+        }
+    }
+
+    /// Replaces the manager with a new `manager` running `strategies`, and drops the actions
+    /// and the annotations of the previous one. Unknown managers are ignored.
+    fn switch_manager(&mut self, manager: &str, strategies: Vec<StrategyChoice>) {
+        let created = strategies.iter().map(StrategyChoice::create).collect();
+        match manager::registry().create(manager, created) {
+            Some(new_manager) => {
+                info!("Switching to the {} manager", manager);
+                self.manager = new_manager;
+                self.manager_name = manager.to_string();
+                self.strategies = strategies;
+                self.action_wrapper.clear_all();
+                self.clear_annotations = true;
+            }
+            None => warn!("Unknown manager requested by a tool: {}", manager),
         }
     }
+
+    /// Restarts the current manager with its strategies, after adding or removing the strategy
+    /// `name` of the robots `robot_ids`.
+    fn update_strategy(&mut self, name: &str, robot_ids: &[u8], start: bool) {
+        let choices: Vec<String> = robot_ids
+            .iter()
+            .map(|id| format!("{}:{}", name, id))
+            .collect();
+        let choices = parse_strategies(&choices);
+        let mut strategies = self.strategies.clone();
+        strategies.retain(|s| !choices.contains(s));
+        if start {
+            strategies.extend(choices);
+        }
+
+        let manager = self.manager_name.clone();
+        self.switch_manager(&manager, strategies);
+    }
 }
 
 impl Component for DecisionPipeline {
@@ -38,16 +124,37 @@ impl Component for DecisionPipeline {
 }
 
 impl DecisionComponent for DecisionPipeline {
+    /// Switches the manager or the strategies when a tool asks for it.
+    fn apply_tool_commands(&mut self, tool_commands: &ToolCommands) {
+        for command in tool_commands {
+            match command {
+                ToolCommand::SwitchManager {
+                    manager,
+                    strategies,
+                } => self.switch_manager(manager, parse_strategies(strategies)),
+                ToolCommand::SetStrategies(strategies) => {
+                    let manager = self.manager_name.clone();
+                    self.switch_manager(&manager, parse_strategies(strategies));
+                }
+                ToolCommand::StartStrategy { name, robot_ids } => {
+                    self.update_strategy(name, robot_ids, true)
+                }
+                ToolCommand::StopStrategy { name, robot_ids } => {
+                    self.update_strategy(name, robot_ids, false)
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Runs a single step of the decision-making pipeline using the given `World` data.
-    fn step(&mut self, world: &World) -> (CommandMap, ToolData) {
-        // TODO : Don't create ToolsData here
-        let mut tool_data = ToolData::default();
+    fn step(&mut self, world: &World, tool_data: &mut ToolData) -> CommandMap {
+        if std::mem::take(&mut self.clear_annotations) {
+            tool_data.annotations.clear();
+        }
+        tool_data.tick(self.clock.now());
         self.manager
-            .step(world, &mut tool_data, &mut self.action_wrapper);
-
-        (
-            self.action_wrapper.compute(world, &mut tool_data),
-            tool_data,
-        )
+            .step(world, tool_data, &mut self.action_wrapper);
+        self.action_wrapper.compute(world, tool_data)
     }
 }
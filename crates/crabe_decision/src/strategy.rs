@@ -1,6 +1,9 @@
 use crate::action::ActionWrapper;
+use crate::strategy::testing::Square;
 use crabe_framework::data::tool::ToolData;
 use crabe_framework::data::world::World;
+use crabe_framework::registry::Registry;
+use std::str::FromStr;
 
 /// The `testing` module contains different strategies used for testing purposes. These strategies
 /// are not meant to be used in an actual game but rather to test specific functionalities or to
@@ -34,3 +37,70 @@ pub trait Strategy {
         action_wrapper: &mut ActionWrapper,
     ) -> bool;
 }
+
+/// Returns the registry of the strategies, which are built with the id of the robot they control.
+pub fn registry() -> Registry<dyn Strategy, u8> {
+    Registry::<dyn Strategy, u8>::new().register("square", |id| Box::new(Square::new(id)))
+}
+
+/// The `StrategyChoice` struct is a strategy selected from the command line, written as `name`
+/// or `name:robot_id` (the robot 0 is used when the id is omitted).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrategyChoice {
+    /// The name of the strategy in the registry.
+    pub name: String,
+    /// The id of the robot controlled by the strategy.
+    pub robot_id: u8,
+}
+
+impl FromStr for StrategyChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, robot_id) = match s.split_once(':') {
+            Some((name, id)) => (
+                name,
+                id.parse()
+                    .map_err(|_| format!("invalid robot id `{}`", id))?,
+            ),
+            None => (s, 0),
+        };
+        let names = registry().names();
+        if !names.contains(&name) {
+            return Err(format!(
+                "unknown strategy `{}` (available: {})",
+                name,
+                names.join(", ")
+            ));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            robot_id,
+        })
+    }
+}
+
+impl StrategyChoice {
+    /// Builds the chosen strategy.
+    pub fn create(&self) -> Box<dyn Strategy> {
+        registry()
+            .create(&self.name, self.robot_id)
+            .expect("strategy names are checked when parsed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategy_choices_are_parsed() {
+        let choice: StrategyChoice = "square:3".parse().unwrap();
+        assert_eq!(choice.name, "square");
+        assert_eq!(choice.robot_id, 3);
+        assert_eq!("square".parse::<StrategyChoice>().unwrap().robot_id, 0);
+        assert!("square:x".parse::<StrategyChoice>().is_err());
+        assert!("dance".parse::<StrategyChoice>().is_err());
+    }
+}
@@ -2,3 +2,8 @@ use std::time::Duration;
 
 pub const PACKET_BUFFER_SIZE: usize = 64;
 pub const ROBOT_TIMEOUT: Duration = Duration::from_secs(2);
+pub const FEEDBACK_TIMEOUT: Duration = Duration::from_millis(500);
+/// Maximum distance in meters between the center of an enemy and the ball for it to control it.
+pub const ENEMY_BALL_CONTROL_DISTANCE: f64 = 0.15;
+/// Maximum angle in radians between the orientation of an enemy and the ball for it to control it.
+pub const ENEMY_BALL_CONTROL_ANGLE: f64 = 0.5;
@@ -4,10 +4,10 @@ use crate::constant;
 use crate::data::camera::{CamBall, CamGeometry, CamRobot};
 use chrono::{DateTime, Utc};
 use constant::PACKET_BUFFER_SIZE;
-use crabe_framework::data::world::{AllyInfo, Ball, EnemyInfo, Robot};
+use crabe_framework::data::plot::Plotter;
+use crabe_framework::data::world::{AllyInfo, Ball, EnemyInfo, GameData, Robot};
 use ringbuffer::ConstGenericRingBuffer;
 use std::collections::HashMap;
-use std::time::Instant;
 
 #[derive(Clone, Debug)]
 pub struct FrameInfo {
@@ -23,6 +23,11 @@ pub struct FilterData {
     pub enemies: TrackedRobotMap<EnemyInfo>,
     pub ball: TrackedBall,
     pub geometry: CamGeometry,
+    pub game_data: GameData,
+    /// The last feedback received from each ally robot, merged in the robots of the `World`.
+    pub feedback: HashMap<u8, AllyInfo>,
+    /// Plots the time series of the filters (velocity estimates, ...) for the tools.
+    pub plotter: Plotter,
 }
 
 pub struct TrackedRobot<T> {
@@ -36,7 +41,7 @@ impl<T: Default> Default for TrackedRobot<T> {
         TrackedRobot {
             packets: ConstGenericRingBuffer::new(),
             data: Robot::<T>::default(),
-            last_update: Utc::now(),
+            last_update: Default::default(),
         }
     }
 }
@@ -44,14 +49,14 @@ impl<T: Default> Default for TrackedRobot<T> {
 pub struct TrackedBall {
     pub packets: ConstGenericRingBuffer<CamBall, PACKET_BUFFER_SIZE>,
     pub data: Ball,
-    pub last_update: Instant,
+    pub last_update: DateTime<Utc>,
 }
 
 impl Default for TrackedBall {
     fn default() -> Self {
         Self {
             packets: ConstGenericRingBuffer::new(),
-            last_update: Instant::now(),
+            last_update: Default::default(),
             data: Default::default(),
         }
     }
@@ -2,9 +2,27 @@ pub mod inactive;
 pub mod passthrough;
 pub mod velocity_acceleration;
 
+use crate::constant;
 use crate::data::FilterData;
+use crate::filter::inactive::InactiveFilter;
+use crate::filter::passthrough::PassthroughFilter;
+use crate::filter::velocity_acceleration::VelocityAccelerationFilter;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::data::world::World;
+use crabe_framework::registry::Registry;
 
 pub trait Filter {
     fn step(&mut self, filter_data: &mut FilterData, world: &World);
 }
+
+/// Returns the registry of the filters, which are built with the clock of the system.
+pub fn registry() -> Registry<dyn Filter, SharedClock> {
+    Registry::<dyn Filter, SharedClock>::new()
+        .register("passthrough", |_| Box::new(PassthroughFilter))
+        .register("inactive", |clock| {
+            Box::new(InactiveFilter::new(constant::ROBOT_TIMEOUT, clock))
+        })
+        .register("velocity-acceleration", |_| {
+            Box::new(VelocityAccelerationFilter)
+        })
+}
@@ -1,40 +1,44 @@
-use crate::constant;
 use crate::data::{FilterData, TrackedRobotMap};
 use crate::filter::Filter;
 use chrono::{DateTime, Utc};
+use crabe_framework::clock::SharedClock;
 use crabe_framework::data::world::World;
+use crabe_framework::parameter::{parameters, Parameter};
 use std::time::Duration;
 
 pub struct InactiveFilter {
-    timeout: Duration,
+    timeout: Parameter<Duration>,
+    clock: SharedClock,
 }
 
 impl InactiveFilter {
-    pub fn new(timeout: Duration) -> Self {
-        Self { timeout }
+    pub fn new(timeout: Duration, clock: SharedClock) -> Self {
+        Self {
+            timeout: parameters().declare(
+                "filter.inactive.timeout",
+                "Time without detection after which a robot is removed, in seconds",
+                timeout,
+                Duration::ZERO,
+                Duration::from_secs(10),
+            ),
+            clock,
+        }
     }
 
     fn purge_inactive<T>(&self, tracked_robots: &mut TrackedRobotMap<T>, now: DateTime<Utc>) {
+        let timeout = self.timeout.get();
         tracked_robots.retain(|_id, robot| {
             // Use std duration as chrono does not support const fn yet
             (now - robot.last_update)
                 .to_std()
-                .map_or(false, |d| d < self.timeout)
+                .is_ok_and(|d| d < timeout)
         });
     }
 }
 
-impl Default for InactiveFilter {
-    fn default() -> Self {
-        Self {
-            timeout: constant::ROBOT_TIMEOUT,
-        }
-    }
-}
-
 impl Filter for InactiveFilter {
     fn step(&mut self, filter_data: &mut FilterData, _world: &World) {
-        let now = Utc::now();
+        let now = self.clock.now();
         self.purge_inactive(&mut filter_data.allies, now);
         self.purge_inactive(&mut filter_data.enemies, now);
     }
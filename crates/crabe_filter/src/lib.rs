@@ -6,23 +6,45 @@ mod pre_filter;
 
 use crate::data::FilterData;
 
-use crate::filter::inactive::InactiveFilter;
-use crate::filter::passthrough::PassthroughFilter;
 use crate::filter::Filter;
-use crate::post_filter::ball::BallFilter;
-use crate::post_filter::geometry::GeometryFilter;
-use crate::post_filter::robot::RobotFilter;
 use crate::post_filter::PostFilter;
+use crate::pre_filter::feedback::FeedbackFilter;
+use crate::pre_filter::game_controller::GameControllerFilter;
+use crate::pre_filter::tracker::TrackerFilter;
 use crate::pre_filter::vision::VisionFilter;
 use crate::pre_filter::PreFilter;
+use clap::builder::PossibleValuesParser;
 use clap::Args;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::component::{Component, FilterComponent};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::data::input::InboundData;
-use crabe_framework::data::world::{TeamColor, World};
+use crabe_framework::data::plot::Plotter;
+use crabe_framework::data::world::{GameData, TeamColor, World};
 
 #[derive(Args)]
-pub struct FilterConfig {}
+pub struct FilterConfig {
+    /// Filters applied to the tracked data, in order.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "passthrough,inactive",
+        value_parser = PossibleValuesParser::new(filter::registry().names())
+    )]
+    pub filters: Vec<String>,
+    /// Post-filters writing the tracked data into the world, in order.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "robot,geometry,ball,game-data,game-state,enemy",
+        value_parser = PossibleValuesParser::new(post_filter::registry().names())
+    )]
+    pub post_filters: Vec<String>,
+    /// Fills the world from the frames of the external tracker rather than from the detections
+    /// of the vision, which then only gives the geometry.
+    #[arg(long, requires = "tracker")]
+    pub tracked: bool,
+}
 
 pub struct FilterPipeline {
     pub pre_filters: Vec<Box<dyn PreFilter>>,
@@ -33,29 +55,39 @@ pub struct FilterPipeline {
 }
 
 impl FilterPipeline {
-    pub fn with_config(_config: FilterConfig, common_config: &CommonConfig) -> Self {
+    pub fn with_config(
+        config: FilterConfig,
+        common_config: &CommonConfig,
+        clock: &SharedClock,
+        plotter: &Plotter,
+    ) -> Self {
+        let team_color = if common_config.yellow {
+            TeamColor::Yellow
+        } else {
+            TeamColor::Blue
+        };
+
         Self {
-            pre_filters: vec![Box::new(VisionFilter::new())],
-            filters: vec![
-                Box::new(PassthroughFilter),
-                Box::<InactiveFilter>::default(),
-            ],
-            post_filters: vec![
-                Box::new(RobotFilter),
-                Box::new(GeometryFilter),
-                Box::new(BallFilter),
+            pre_filters: vec![
+                // The game controller tells on which half we are, before the vision data is
+                // expressed in the `World` frame.
+                Box::new(GameControllerFilter::new(clock.clone())),
+                Box::new(VisionFilter::new(clock.clone(), !config.tracked)),
+                Box::new(TrackerFilter::new(clock.clone(), config.tracked)),
+                Box::new(FeedbackFilter::new(clock.clone())),
             ],
+            filters: filter::registry().create_all(&config.filters, clock.clone()),
+            post_filters: post_filter::registry().create_all(&config.post_filters, clock.clone()),
             filter_data: FilterData {
                 allies: Default::default(),
                 enemies: Default::default(),
                 ball: Default::default(),
                 geometry: Default::default(),
+                game_data: GameData::new(team_color),
+                feedback: Default::default(),
+                plotter: plotter.clone(),
             },
-            team_color: if common_config.yellow {
-                TeamColor::Yellow
-            } else {
-                TeamColor::Blue
-            },
+            team_color,
         }
     }
 }
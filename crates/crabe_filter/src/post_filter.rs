@@ -1,10 +1,32 @@
 pub mod ball;
+pub mod enemy;
+pub mod game_data;
+pub mod game_state;
 pub mod geometry;
 pub mod robot;
 
 use crate::data::FilterData;
+use crate::post_filter::ball::BallFilter;
+use crate::post_filter::enemy::EnemyFilter;
+use crate::post_filter::game_data::GameDataFilter;
+use crate::post_filter::game_state::GameStateFilter;
+use crate::post_filter::geometry::GeometryFilter;
+use crate::post_filter::robot::RobotFilter;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::data::world::World;
+use crabe_framework::registry::Registry;
 
 pub trait PostFilter {
     fn step(&mut self, filter_data: &FilterData, world: &mut World);
 }
+
+/// Returns the registry of the post-filters, which are built with the clock of the system.
+pub fn registry() -> Registry<dyn PostFilter, SharedClock> {
+    Registry::<dyn PostFilter, SharedClock>::new()
+        .register("robot", |_| Box::new(RobotFilter))
+        .register("geometry", |_| Box::new(GeometryFilter))
+        .register("ball", |_| Box::new(BallFilter))
+        .register("game-data", |_| Box::new(GameDataFilter))
+        .register("game-state", |clock| Box::new(GameStateFilter::new(clock)))
+        .register("enemy", |_| Box::new(EnemyFilter))
+}
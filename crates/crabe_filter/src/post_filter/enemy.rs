@@ -0,0 +1,87 @@
+use crate::constant::{ENEMY_BALL_CONTROL_ANGLE, ENEMY_BALL_CONTROL_DISTANCE};
+use crate::data::FilterData;
+use crate::post_filter::PostFilter;
+use crabe_framework::data::world::{Ball, EnemyInfo, Robot, World};
+use nalgebra::{Point2, Vector2};
+
+/// The `EnemyFilter` post-filter infers the `EnemyInfo` of the enemies from the robots, the ball
+/// and the referee data already in the `World`, so it must run after the other post-filters.
+pub struct EnemyFilter;
+
+/// Returns whether the `robot` controls the `ball`, i.e. the ball is close to the robot and in
+/// front of it.
+fn controls_ball(robot: &Robot<EnemyInfo>, kick_direction: &Vector2<f64>, ball: &Ball) -> bool {
+    let to_ball = ball.position_2d() - robot.pose.position;
+    to_ball.norm() <= ENEMY_BALL_CONTROL_DISTANCE
+        && (to_ball.norm() == 0.0 || kick_direction.angle(&to_ball) <= ENEMY_BALL_CONTROL_ANGLE)
+}
+
+/// Returns the threat of a robot for our goal, between 0 and 1. Half of the score comes from
+/// the proximity to our goal, the other half is only given to the robot controlling the ball,
+/// depending on how much it is aiming at our goal.
+fn threat(
+    position: &Point2<f64>,
+    kick_direction: &Vector2<f64>,
+    controls_ball: bool,
+    ally_goal: &Point2<f64>,
+    field_length: f64,
+) -> f64 {
+    let to_goal = ally_goal - position;
+    let proximity = 1.0 - (to_goal.norm() / field_length).clamp(0.0, 1.0);
+    let aim = if controls_ball && to_goal.norm() > 0.0 {
+        kick_direction.dot(&to_goal.normalize()).max(0.0)
+    } else {
+        0.0
+    };
+    0.5 * proximity + 0.5 * aim
+}
+
+impl PostFilter for EnemyFilter {
+    fn step(&mut self, _filter_data: &FilterData, world: &mut World) {
+        let ally_goal = Point2::new(-world.geometry.field.length / 2.0, 0.0);
+        let goalkeeper = world.data.enemy.goalkeeper;
+
+        for (id, robot) in world.enemies_bot.iter_mut() {
+            let orientation = robot.pose.orientation;
+            let kick_direction = Vector2::new(orientation.cos(), orientation.sin());
+            let controls_ball = world
+                .ball
+                .as_ref()
+                .is_some_and(|ball| controls_ball(robot, &kick_direction, ball));
+
+            robot.has_ball = controls_ball;
+            robot.robot_info = EnemyInfo {
+                is_goalkeeper: goalkeeper == Some(*id),
+                controls_ball,
+                kick_direction,
+                threat: threat(
+                    &robot.pose.position,
+                    &kick_direction,
+                    controls_ball,
+                    &ally_goal,
+                    world.geometry.field.length,
+                ),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attacker_aiming_at_our_goal_is_a_threat() {
+        let ally_goal = Point2::new(-4.5, 0.0);
+        let position = Point2::new(-3.0, 0.0);
+        let facing_goal = Vector2::new(-1.0, 0.0);
+        let facing_away = Vector2::new(1.0, 0.0);
+
+        let aiming = threat(&position, &facing_goal, true, &ally_goal, 9.0);
+        let turned = threat(&position, &facing_away, true, &ally_goal, 9.0);
+        let without_ball = threat(&position, &facing_goal, false, &ally_goal, 9.0);
+        assert!(aiming > without_ball);
+        assert_eq!(turned, without_ball);
+        assert!(aiming <= 1.0);
+    }
+}
@@ -0,0 +1,32 @@
+use crate::data::FilterData;
+use crate::post_filter::PostFilter;
+use crabe_framework::clock::SharedClock;
+use crabe_framework::data::referee::GameStateMachine;
+use crabe_framework::data::world::World;
+
+/// The `GameStateFilter` derives the high-level game state from the referee state and the ball.
+/// It must run after the filters that update the referee and the ball of the `World`.
+pub struct GameStateFilter {
+    machine: GameStateMachine,
+    clock: SharedClock,
+}
+
+impl GameStateFilter {
+    pub fn new(clock: SharedClock) -> Self {
+        Self {
+            machine: Default::default(),
+            clock,
+        }
+    }
+}
+
+impl PostFilter for GameStateFilter {
+    fn step(&mut self, _filter_data: &FilterData, world: &mut World) {
+        world.data.state = self.machine.update(
+            &world.data.referee,
+            world.ball.as_ref(),
+            world.team_color,
+            self.clock.now(),
+        );
+    }
+}
@@ -18,5 +18,13 @@ impl PostFilter for RobotFilter {
     fn step(&mut self, filter_data: &FilterData, world: &mut World) {
         insert_tracked(&mut world.enemies_bot, filter_data.enemies.iter());
         insert_tracked(&mut world.allies_bot, filter_data.allies.iter());
+
+        // The vision only sees the robots, what they feel comes from their feedback.
+        world.allies_bot.iter_mut().for_each(|(id, robot)| {
+            if let Some(info) = filter_data.feedback.get(id) {
+                robot.has_ball = info.has_ball;
+                robot.robot_info = info.clone();
+            }
+        });
     }
 }
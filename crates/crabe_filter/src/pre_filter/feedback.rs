@@ -0,0 +1,97 @@
+use crate::constant::FEEDBACK_TIMEOUT;
+use crate::data::FilterData;
+use crate::pre_filter::PreFilter;
+use crabe_framework::clock::SharedClock;
+use crabe_framework::constant::MAX_ID_ROBOTS;
+use crabe_framework::data::input::InboundData;
+use crabe_framework::data::world::TeamColor;
+use log::warn;
+
+/// The `FeedbackFilter` pre-filter keeps the last feedback received from each ally robot.
+/// The ball detection of a robot is considered lost when it hasn't sent feedback for
+/// `FEEDBACK_TIMEOUT`, the other values are kept as the last known ones.
+pub struct FeedbackFilter {
+    clock: SharedClock,
+}
+
+impl FeedbackFilter {
+    pub fn new(clock: SharedClock) -> Self {
+        Self { clock }
+    }
+}
+
+impl PreFilter for FeedbackFilter {
+    fn step(
+        &mut self,
+        inbound_data: &InboundData,
+        _team_color: &TeamColor,
+        filter_data: &mut FilterData,
+    ) {
+        let now = self.clock.now();
+        for (id, feedback) in &inbound_data.feedback {
+            if *id > MAX_ID_ROBOTS as u32 {
+                warn!("Feedback received from an invalid robot id: {}", id);
+                continue;
+            }
+
+            let info = filter_data.feedback.entry(*id as u8).or_default();
+            info.has_ball = feedback.has_ball;
+            info.voltage = feedback.voltage.or(info.voltage);
+            info.motor_speeds = feedback.motor_speeds.or(info.motor_speeds);
+            info.last_feedback = Some(now);
+        }
+
+        filter_data.feedback.values_mut().for_each(|info| {
+            let expired = info.last_feedback.is_some_and(|last| {
+                (now - last)
+                    .to_std()
+                    .is_ok_and(|elapsed| elapsed > FEEDBACK_TIMEOUT)
+            });
+            if expired {
+                info.has_ball = false;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::clock::ManualClock;
+    use crabe_framework::data::output::Feedback;
+    use crabe_framework::data::plot::Plotter;
+    use crabe_framework::data::world::GameData;
+    use std::sync::Arc;
+
+    #[test]
+    fn ball_is_lost_without_feedback() {
+        let clock = Arc::new(ManualClock::default());
+        let mut filter = FeedbackFilter::new(clock.clone());
+        let mut filter_data = FilterData {
+            allies: Default::default(),
+            enemies: Default::default(),
+            ball: Default::default(),
+            geometry: Default::default(),
+            game_data: GameData::new(TeamColor::Blue),
+            feedback: Default::default(),
+            plotter: Plotter::new(clock.clone()),
+        };
+        let mut inbound_data = InboundData::default();
+        inbound_data.feedback.insert(
+            3,
+            Feedback {
+                has_ball: true,
+                voltage: Some(15.8),
+                ..Default::default()
+            },
+        );
+
+        filter.step(&inbound_data, &TeamColor::Blue, &mut filter_data);
+        assert!(filter_data.feedback[&3].has_ball);
+
+        clock.advance(FEEDBACK_TIMEOUT * 2);
+        filter.step(&InboundData::default(), &TeamColor::Blue, &mut filter_data);
+        assert!(!filter_data.feedback[&3].has_ball);
+        assert_eq!(filter_data.feedback[&3].voltage, Some(15.8));
+    }
+}
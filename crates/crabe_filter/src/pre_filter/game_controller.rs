@@ -0,0 +1,412 @@
+use crate::data::FilterData;
+use crate::pre_filter::PreFilter;
+use chrono::{DateTime, TimeZone, Utc};
+use crabe_framework::clock::SharedClock;
+use crabe_framework::data::input::InboundData;
+use crabe_framework::data::referee::{Referee, RefereeCommand, Stage};
+use crabe_framework::data::world::{Team, TeamColor};
+use crabe_protocol::protobuf::game_controller_packet::{
+    referee, Referee as RefereePacket, Team as TeamPacket,
+};
+use log::warn;
+use nalgebra::Point2;
+
+mod event {
+    use super::{create_date_time, team_color};
+    use crabe_framework::data::referee::{GameEvent, GameEventKind};
+    use crabe_protocol::protobuf::game_controller_packet::game_event::Event;
+    use crabe_protocol::protobuf::game_controller_packet::{GameEvent as GameEventPacket, Vector2};
+    use nalgebra::Point2;
+
+    fn location(vector: &Option<Vector2>) -> Option<Point2<f64>> {
+        vector.as_ref().map(|v| Point2::new(v.x as f64, v.y as f64))
+    }
+
+    fn kind(event: &Event) -> GameEventKind {
+        match event {
+            Event::BallLeftFieldTouchLine(_) => GameEventKind::BallLeftFieldTouchLine,
+            Event::BallLeftFieldGoalLine(_) => GameEventKind::BallLeftFieldGoalLine,
+            Event::AimlessKick(_) => GameEventKind::AimlessKick,
+            Event::AttackerTooCloseToDefenseArea(_) => GameEventKind::AttackerTooCloseToDefenseArea,
+            Event::DefenderInDefenseArea(_) => GameEventKind::DefenderInDefenseArea,
+            Event::BoundaryCrossing(_) => GameEventKind::BoundaryCrossing,
+            Event::KeeperHeldBall(_) => GameEventKind::KeeperHeldBall,
+            Event::BotDribbledBallTooFar(_) => GameEventKind::BotDribbledBallTooFar,
+            Event::BotPushedBot(_) | Event::BotPushedBotSkipped(_) => GameEventKind::BotPushedBot,
+            Event::BotHeldBallDeliberately(_) => GameEventKind::BotHeldBallDeliberately,
+            Event::BotTippedOver(_) => GameEventKind::BotTippedOver,
+            Event::AttackerTouchedBallInDefenseArea(_) => {
+                GameEventKind::AttackerTouchedBallInDefenseArea
+            }
+            Event::BotKickedBallTooFast(_) => GameEventKind::BotKickedBallTooFast,
+            Event::BotCrashUnique(_) | Event::BotCrashUniqueSkipped(_) => {
+                GameEventKind::BotCrashUnique
+            }
+            Event::BotCrashDrawn(_) => GameEventKind::BotCrashDrawn,
+            Event::DefenderTooCloseToKickPoint(_) => GameEventKind::DefenderTooCloseToKickPoint,
+            Event::BotTooFastInStop(_) => GameEventKind::BotTooFastInStop,
+            Event::BotInterferedPlacement(_) => GameEventKind::BotInterferedPlacement,
+            Event::PossibleGoal(_) => GameEventKind::PossibleGoal,
+            Event::Goal(_) => GameEventKind::Goal,
+            Event::InvalidGoal(_) => GameEventKind::InvalidGoal,
+            Event::AttackerDoubleTouchedBall(_) => GameEventKind::AttackerDoubleTouchedBall,
+            Event::PlacementSucceeded(_) => GameEventKind::PlacementSucceeded,
+            Event::PenaltyKickFailed(_) => GameEventKind::PenaltyKickFailed,
+            Event::NoProgressInGame(_) => GameEventKind::NoProgressInGame,
+            Event::PlacementFailed(_) => GameEventKind::PlacementFailed,
+            Event::MultipleCards(_) => GameEventKind::MultipleCards,
+            Event::MultipleFouls(_) => GameEventKind::MultipleFouls,
+            Event::BotSubstitution(_) => GameEventKind::BotSubstitution,
+            Event::TooManyRobots(_) => GameEventKind::TooManyRobots,
+            Event::ChallengeFlag(_) => GameEventKind::ChallengeFlag,
+            Event::ChallengeFlagHandled(_) => GameEventKind::ChallengeFlagHandled,
+            Event::EmergencyStop(_) => GameEventKind::EmergencyStop,
+            Event::UnsportingBehaviorMinor(_) => GameEventKind::UnsportingBehaviorMinor,
+            Event::UnsportingBehaviorMajor(_) => GameEventKind::UnsportingBehaviorMajor,
+            Event::Prepared(_) => GameEventKind::Prepared,
+            Event::IndirectGoal(_) => GameEventKind::IndirectGoal,
+            Event::ChippedGoal(_) => GameEventKind::ChippedGoal,
+            Event::KickTimeout(_) => GameEventKind::KickTimeout,
+            Event::AttackerTouchedOpponentInDefenseArea(_)
+            | Event::AttackerTouchedOpponentInDefenseAreaSkipped(_) => {
+                GameEventKind::AttackerTouchedOpponentInDefenseArea
+            }
+            Event::DefenderInDefenseAreaPartially(_) => {
+                GameEventKind::DefenderInDefenseAreaPartially
+            }
+            Event::MultiplePlacementFailures(_) => GameEventKind::MultiplePlacementFailures,
+        }
+    }
+
+    /// Extracts the team, the robot and the location of the event, when the event has them.
+    fn origin(event: &Event) -> (Option<i32>, Option<u32>, Option<Point2<f64>>) {
+        match event {
+            Event::BallLeftFieldTouchLine(e) | Event::BallLeftFieldGoalLine(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::AimlessKick(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::AttackerTooCloseToDefenseArea(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::DefenderInDefenseArea(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::BotDribbledBallTooFar(e) => (Some(e.by_team), e.by_bot, location(&e.start)),
+            Event::BotHeldBallDeliberately(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::BotTippedOver(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::AttackerTouchedBallInDefenseArea(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::BotKickedBallTooFast(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::DefenderTooCloseToKickPoint(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::BotTooFastInStop(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::BotInterferedPlacement(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::AttackerDoubleTouchedBall(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::IndirectGoal(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::ChippedGoal(e) => (Some(e.by_team), e.by_bot, location(&e.location)),
+            Event::DefenderInDefenseAreaPartially(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::AttackerTouchedOpponentInDefenseArea(e)
+            | Event::AttackerTouchedOpponentInDefenseAreaSkipped(e) => {
+                (Some(e.by_team), e.by_bot, location(&e.location))
+            }
+            Event::BotPushedBot(e) | Event::BotPushedBotSkipped(e) => {
+                (Some(e.by_team), e.violator, location(&e.location))
+            }
+            Event::BotCrashUnique(e) | Event::BotCrashUniqueSkipped(e) => {
+                (Some(e.by_team), e.violator, location(&e.location))
+            }
+            Event::PossibleGoal(e) | Event::Goal(e) | Event::InvalidGoal(e) => {
+                (Some(e.by_team), e.kicking_bot, location(&e.location))
+            }
+            Event::BotCrashDrawn(e) => (None, None, location(&e.location)),
+            Event::BoundaryCrossing(e) => (Some(e.by_team), None, location(&e.location)),
+            Event::KeeperHeldBall(e) => (Some(e.by_team), None, location(&e.location)),
+            Event::PenaltyKickFailed(e) => (Some(e.by_team), None, location(&e.location)),
+            Event::KickTimeout(e) => (Some(e.by_team), None, location(&e.location)),
+            Event::TooManyRobots(e) => (Some(e.by_team), None, location(&e.ball_location)),
+            Event::NoProgressInGame(e) => (None, None, location(&e.location)),
+            Event::PlacementSucceeded(e) => (Some(e.by_team), None, None),
+            Event::PlacementFailed(e) => (Some(e.by_team), None, None),
+            Event::MultipleCards(e) => (Some(e.by_team), None, None),
+            Event::MultipleFouls(e) => (Some(e.by_team), None, None),
+            Event::MultiplePlacementFailures(e) => (Some(e.by_team), None, None),
+            Event::BotSubstitution(e) => (Some(e.by_team), None, None),
+            Event::ChallengeFlag(e) => (Some(e.by_team), None, None),
+            Event::ChallengeFlagHandled(e) => (Some(e.by_team), None, None),
+            Event::EmergencyStop(e) => (Some(e.by_team), None, None),
+            Event::UnsportingBehaviorMinor(e) => (Some(e.by_team), None, None),
+            Event::UnsportingBehaviorMajor(e) => (Some(e.by_team), None, None),
+            Event::Prepared(_) => (None, None, None),
+        }
+    }
+
+    pub fn to_game_event(packet: &GameEventPacket) -> Option<GameEvent> {
+        let event = packet.event.as_ref()?;
+        let (by_team, by_bot, location) = origin(event);
+        Some(GameEvent {
+            kind: kind(event),
+            by_team: by_team.and_then(team_color),
+            by_bot: by_bot.map(|id| id as u8),
+            location,
+            created: packet.created_timestamp.and_then(create_date_time),
+        })
+    }
+}
+
+/// Converts a timestamp of the game controller in microseconds, none if it is invalid.
+fn create_date_time(micros: u64) -> Option<DateTime<Utc>> {
+    let date_time = Utc.timestamp_micros(micros as i64).single();
+    if date_time.is_none() {
+        warn!("Invalid game controller timestamp: {}", micros);
+    }
+    date_time
+}
+
+fn team_color(team: i32) -> Option<TeamColor> {
+    match TeamPacket::try_from(team) {
+        Ok(TeamPacket::Yellow) => Some(TeamColor::Yellow),
+        Ok(TeamPacket::Blue) => Some(TeamColor::Blue),
+        _ => None,
+    }
+}
+
+fn micros_to_secs(micros: i32) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+fn map_stage(stage: i32) -> Stage {
+    match referee::Stage::try_from(stage) {
+        Ok(referee::Stage::NormalFirstHalfPre) => Stage::NormalFirstHalfPre,
+        Ok(referee::Stage::NormalFirstHalf) => Stage::NormalFirstHalf,
+        Ok(referee::Stage::NormalHalfTime) => Stage::NormalHalfTime,
+        Ok(referee::Stage::NormalSecondHalfPre) => Stage::NormalSecondHalfPre,
+        Ok(referee::Stage::NormalSecondHalf) => Stage::NormalSecondHalf,
+        Ok(referee::Stage::ExtraTimeBreak) => Stage::ExtraTimeBreak,
+        Ok(referee::Stage::ExtraFirstHalfPre) => Stage::ExtraFirstHalfPre,
+        Ok(referee::Stage::ExtraFirstHalf) => Stage::ExtraFirstHalf,
+        Ok(referee::Stage::ExtraHalfTime) => Stage::ExtraHalfTime,
+        Ok(referee::Stage::ExtraSecondHalfPre) => Stage::ExtraSecondHalfPre,
+        Ok(referee::Stage::ExtraSecondHalf) => Stage::ExtraSecondHalf,
+        Ok(referee::Stage::PenaltyShootoutBreak) => Stage::PenaltyShootoutBreak,
+        Ok(referee::Stage::PenaltyShootout) => Stage::PenaltyShootout,
+        Ok(referee::Stage::PostGame) => Stage::PostGame,
+        Err(_) => {
+            warn!("Unknown referee stage: {}", stage);
+            Stage::default()
+        }
+    }
+}
+
+fn map_command(command: i32) -> RefereeCommand {
+    use referee::Command;
+    match Command::try_from(command) {
+        Ok(Command::Halt) => RefereeCommand::Halt,
+        Ok(Command::Stop) => RefereeCommand::Stop,
+        Ok(Command::NormalStart) => RefereeCommand::NormalStart,
+        Ok(Command::ForceStart) => RefereeCommand::ForceStart,
+        Ok(Command::PrepareKickoffYellow) => RefereeCommand::PrepareKickoff(TeamColor::Yellow),
+        Ok(Command::PrepareKickoffBlue) => RefereeCommand::PrepareKickoff(TeamColor::Blue),
+        Ok(Command::PreparePenaltyYellow) => RefereeCommand::PreparePenalty(TeamColor::Yellow),
+        Ok(Command::PreparePenaltyBlue) => RefereeCommand::PreparePenalty(TeamColor::Blue),
+        Ok(Command::DirectFreeYellow) => RefereeCommand::DirectFree(TeamColor::Yellow),
+        Ok(Command::DirectFreeBlue) => RefereeCommand::DirectFree(TeamColor::Blue),
+        Ok(Command::IndirectFreeYellow) => RefereeCommand::IndirectFree(TeamColor::Yellow),
+        Ok(Command::IndirectFreeBlue) => RefereeCommand::IndirectFree(TeamColor::Blue),
+        Ok(Command::TimeoutYellow) => RefereeCommand::Timeout(TeamColor::Yellow),
+        Ok(Command::TimeoutBlue) => RefereeCommand::Timeout(TeamColor::Blue),
+        Ok(Command::GoalYellow) => RefereeCommand::Goal(TeamColor::Yellow),
+        Ok(Command::GoalBlue) => RefereeCommand::Goal(TeamColor::Blue),
+        Ok(Command::BallPlacementYellow) => RefereeCommand::BallPlacement(TeamColor::Yellow),
+        Ok(Command::BallPlacementBlue) => RefereeCommand::BallPlacement(TeamColor::Blue),
+        Err(_) => {
+            warn!("Unknown referee command: {}", command);
+            RefereeCommand::Halt
+        }
+    }
+}
+
+fn update_team(team: &mut Team, info: &referee::TeamInfo) {
+    team.name = if info.name.is_empty() {
+        None
+    } else {
+        Some(info.name.clone())
+    };
+    team.score = info.score;
+    team.red_cards = info.red_cards;
+    team.yellow_cards = info.yellow_cards;
+    team.yellow_card_times = info
+        .yellow_card_times
+        .iter()
+        .map(|t| *t as f64 / 1_000_000.0)
+        .collect();
+    team.timeouts = info.timeouts;
+    team.timeout_time = info.timeout_time as f64 / 1_000_000.0;
+    team.goalkeeper = Some(info.goalkeeper as u8);
+    team.max_allowed_bots = info.max_allowed_bots;
+}
+
+/// Creates the referee state of `packet`, received at `now`.
+fn create_referee(packet: &RefereePacket, now: DateTime<Utc>) -> Referee {
+    Referee {
+        stage: map_stage(packet.stage),
+        stage_time_left: packet.stage_time_left.map(micros_to_secs),
+        command: map_command(packet.command),
+        next_command: packet.next_command.map(map_command),
+        command_counter: packet.command_counter,
+        command_timestamp: create_date_time(packet.command_timestamp).unwrap_or(now),
+        designated_position: packet
+            .designated_position
+            .as_ref()
+            .map(|p| Point2::new(p.x as f64 / 1000.0, p.y as f64 / 1000.0)),
+        action_time_left: packet.current_action_time_remaining.map(micros_to_secs),
+        game_events: packet
+            .game_events
+            .iter()
+            .filter_map(event::to_game_event)
+            .collect(),
+    }
+}
+
+/// The `GameControllerFilter` pre-filter translates the last referee packet received from the
+/// game controller into the referee state and the team information of the `GameData`.
+pub struct GameControllerFilter {
+    clock: SharedClock,
+}
+
+impl GameControllerFilter {
+    pub fn new(clock: SharedClock) -> Self {
+        Self { clock }
+    }
+}
+
+impl PreFilter for GameControllerFilter {
+    fn step(
+        &mut self,
+        inbound_data: &InboundData,
+        team_color: &TeamColor,
+        filter_data: &mut FilterData,
+    ) {
+        // Each packet contains the whole state of the game controller,
+        // only the last one is relevant.
+        if let Some(packet) = inbound_data.gc_packet.last() {
+            let (ally, enemy) = match team_color {
+                TeamColor::Yellow => (&packet.yellow, &packet.blue),
+                TeamColor::Blue => (&packet.blue, &packet.yellow),
+            };
+            let game_data = &mut filter_data.game_data;
+            update_team(&mut game_data.ally, ally);
+            update_team(&mut game_data.enemy, enemy);
+            if let Some(blue_on_positive_half) = packet.blue_team_on_positive_half {
+                game_data.positive_half = if blue_on_positive_half {
+                    TeamColor::Blue
+                } else {
+                    TeamColor::Yellow
+                };
+            }
+
+            // The positions sent by the game controller are raw field coordinates,
+            // they are expressed in the `World` frame like the vision data.
+            let mut referee = create_referee(packet, self.clock.now());
+            referee.designated_position = referee
+                .designated_position
+                .map(|p| game_data.mirror_point(p));
+            referee.game_events.iter_mut().for_each(|event| {
+                event.location = event.location.map(|p| game_data.mirror_point(p));
+            });
+            game_data.referee = referee;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::clock::{Clock, ManualClock};
+    use crabe_framework::data::plot::Plotter;
+    use crabe_framework::data::referee::GameEventKind;
+    use crabe_framework::data::world::GameData;
+    use crabe_protocol::protobuf::game_controller_packet::game_event::{BallLeftField, Event};
+    use crabe_protocol::protobuf::game_controller_packet::{GameEvent, Vector2};
+    use std::sync::Arc;
+
+    fn team(name: &str, goalkeeper: u32, max_allowed_bots: u32) -> referee::TeamInfo {
+        referee::TeamInfo {
+            name: name.to_string(),
+            score: 1,
+            goalkeeper,
+            max_allowed_bots: Some(max_allowed_bots),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn referee_packets_fill_the_game_data() {
+        let clock = Arc::new(ManualClock::default());
+        let mut filter = GameControllerFilter::new(clock.clone());
+        let mut filter_data = FilterData {
+            allies: Default::default(),
+            enemies: Default::default(),
+            ball: Default::default(),
+            geometry: Default::default(),
+            game_data: GameData::new(TeamColor::Blue),
+            feedback: Default::default(),
+            plotter: Plotter::new(clock.clone()),
+        };
+        let packet = RefereePacket {
+            command: referee::Command::BallPlacementYellow as i32,
+            command_timestamp: i64::MAX as u64,
+            yellow: team("yellow", 2, 5),
+            blue: team("blue", 4, 6),
+            designated_position: Some(referee::Point {
+                x: 1500.0,
+                y: -500.0,
+            }),
+            // Our team plays on the positive half, the raw coordinates are mirrored.
+            blue_team_on_positive_half: Some(true),
+            game_events: vec![GameEvent {
+                created_timestamp: Some(1_000_000),
+                event: Some(Event::BallLeftFieldTouchLine(BallLeftField {
+                    by_team: TeamPacket::Yellow as i32,
+                    by_bot: Some(3),
+                    location: Some(Vector2 { x: 1.0, y: 2.0 }),
+                })),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let inbound_data = InboundData {
+            gc_packet: vec![packet],
+            ..Default::default()
+        };
+
+        filter.step(&inbound_data, &TeamColor::Blue, &mut filter_data);
+
+        let game_data = &filter_data.game_data;
+        assert_eq!(game_data.ally.name.as_deref(), Some("blue"));
+        assert_eq!(game_data.ally.goalkeeper, Some(4));
+        assert_eq!(game_data.ally.max_allowed_bots, Some(6));
+        assert_eq!(game_data.enemy.name.as_deref(), Some("yellow"));
+        assert_eq!(game_data.enemy.goalkeeper, Some(2));
+        assert_eq!(game_data.enemy.max_allowed_bots, Some(5));
+        assert_eq!(game_data.positive_half, TeamColor::Blue);
+
+        let referee = &game_data.referee;
+        assert_eq!(
+            referee.command,
+            RefereeCommand::BallPlacement(TeamColor::Yellow)
+        );
+        // The invalid timestamp is replaced by the time of reception.
+        assert_eq!(referee.command_timestamp, clock.now());
+        assert_eq!(referee.designated_position, Some(Point2::new(-1.5, 0.5)));
+        let event = &referee.game_events[0];
+        assert_eq!(event.kind, GameEventKind::BallLeftFieldTouchLine);
+        assert_eq!(event.by_team, Some(TeamColor::Yellow));
+        assert_eq!(event.by_bot, Some(3));
+        assert_eq!(event.location, Some(Point2::new(-1.0, -2.0)));
+        assert_eq!(event.created, Utc.timestamp_opt(1, 0).single());
+    }
+}
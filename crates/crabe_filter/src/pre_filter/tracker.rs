@@ -0,0 +1,347 @@
+use crate::data::{FilterData, TrackedBall, TrackedRobotMap};
+use crate::pre_filter::PreFilter;
+use chrono::{DateTime, Duration, Utc};
+use crabe_framework::clock::SharedClock;
+use crabe_framework::constant::MAX_ID_ROBOTS;
+use crabe_framework::data::input::InboundData;
+use crabe_framework::data::plot::Plotter;
+use crabe_framework::data::world::{
+    Ball, BallKick, GameData, Kicker, Pose, Robot, RobotVelocity, TeamColor,
+};
+use crabe_protocol::protobuf::tracked_vision_packet::{
+    KickedBall, RobotId, TeamColor as TrackedTeamColor, TrackedFrame,
+    TrackedRobot as TrackedFrameRobot, Vector2,
+};
+use log::{info, warn};
+use nalgebra::{Point2, Point3, Vector3};
+
+/// Returns whether the robot `robot_id` is an ally, or none if its color is unknown.
+fn is_ally(robot_id: &RobotId, team_color: &TeamColor) -> Option<bool> {
+    let color = match TrackedTeamColor::try_from(robot_id.team_color) {
+        Ok(TrackedTeamColor::Yellow) => TeamColor::Yellow,
+        Ok(TrackedTeamColor::Blue) => TeamColor::Blue,
+        _ => return None,
+    };
+    Some(color == *team_color)
+}
+
+fn mirror_vector(game_data: &GameData, v: &Vector2) -> Point2<f64> {
+    game_data.mirror_point(Point2::new(v.x as f64, v.y as f64))
+}
+
+fn to_robot<T: Default>(
+    robot: &TrackedFrameRobot,
+    game_data: &GameData,
+    now: DateTime<Utc>,
+) -> Robot<T> {
+    let velocity = robot
+        .vel
+        .as_ref()
+        .map(|v| mirror_vector(game_data, v).coords)
+        .unwrap_or_default();
+    Robot {
+        id: robot.robot_id.id as u8,
+        has_ball: false,
+        robot_info: T::default(),
+        pose: Pose::new(
+            mirror_vector(game_data, &robot.pos),
+            game_data.mirror_orientation(robot.orientation as f64),
+        ),
+        velocity: RobotVelocity {
+            linear: velocity,
+            // Mirroring turns the field by half a turn, which keeps the angular velocities.
+            angular: robot.vel_angular.unwrap_or(0.0) as f64,
+        },
+        acceleration: Default::default(),
+        timestamp: now,
+    }
+}
+
+/// The `TrackerFilter` pre-filter reads the tracked frames of an external tracker, which already
+/// estimates the velocities of the robots and the ball and detects the kicks.
+///
+/// When it fills the world, the robots and the ball are written directly in the tracked data,
+/// replacing the detections of the vision. Otherwise, the velocities of the tracker are plotted
+/// under `tracker/`, to compare them with the estimates of our filters.
+///
+/// Only the first tracker heard from is followed, several trackers may broadcast on the network.
+pub struct TrackerFilter {
+    clock: SharedClock,
+    fill: bool,
+    source: Option<String>,
+}
+
+impl TrackerFilter {
+    pub fn new(clock: SharedClock, fill: bool) -> Self {
+        Self {
+            clock,
+            fill,
+            source: None,
+        }
+    }
+
+    /// Converts the tracker timestamp `timestamp` to our clock, from the timestamp of the frame
+    /// received at `now`. The clock of the tracker may not be synchronized with ours.
+    fn to_local_time(frame: &TrackedFrame, timestamp: f64, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::microseconds(((frame.timestamp - timestamp) * 1e6) as i64)
+    }
+
+    fn to_kick(
+        kick: &KickedBall,
+        frame: &TrackedFrame,
+        team_color: &TeamColor,
+        game_data: &GameData,
+        now: DateTime<Utc>,
+    ) -> BallKick {
+        let factor = game_data.side_factor();
+        let kicker = kick.robot_id.as_ref().and_then(|robot_id| {
+            let id = robot_id.id as u8;
+            is_ally(robot_id, team_color).map(|ally| {
+                if ally {
+                    Kicker::Ally(id)
+                } else {
+                    Kicker::Enemy(id)
+                }
+            })
+        });
+        BallKick {
+            kicker,
+            position: mirror_vector(game_data, &kick.pos),
+            velocity: Vector3::new(
+                factor * kick.vel.x as f64,
+                factor * kick.vel.y as f64,
+                kick.vel.z as f64,
+            ),
+            start: Self::to_local_time(frame, kick.start_timestamp, now),
+            stop: kick
+                .stop_timestamp
+                .map(|stop| Self::to_local_time(frame, stop, now)),
+            stop_position: kick.stop_pos.as_ref().map(|p| mirror_vector(game_data, p)),
+        }
+    }
+
+    fn fill_robots<'a, T: Default>(
+        tracked: &mut TrackedRobotMap<T>,
+        robots: impl Iterator<Item = &'a TrackedFrameRobot>,
+        game_data: &GameData,
+        now: DateTime<Utc>,
+    ) {
+        // The frames hold all the robots the tracker still sees.
+        let robots: Vec<_> = robots.collect();
+        tracked.retain(|id, _| robots.iter().any(|r| r.robot_id.id == *id as u32));
+        for robot in robots {
+            let tracked = tracked.entry(robot.robot_id.id as u8).or_default();
+            tracked.data = to_robot(robot, game_data, now);
+            tracked.last_update = now;
+        }
+    }
+
+    fn fill_ball(
+        tracked: &mut TrackedBall,
+        frame: &TrackedFrame,
+        team_color: &TeamColor,
+        game_data: &GameData,
+        now: DateTime<Utc>,
+    ) {
+        // The first ball is the primary one.
+        let Some(ball) = frame.balls.first() else {
+            return;
+        };
+        let factor = game_data.side_factor();
+        let velocity = ball
+            .vel
+            .as_ref()
+            .map(|v| Vector3::new(factor * v.x as f64, factor * v.y as f64, v.z as f64))
+            .unwrap_or_default();
+        tracked.data = Ball {
+            position: Point3::new(
+                factor * ball.pos.x as f64,
+                factor * ball.pos.y as f64,
+                ball.pos.z as f64,
+            ),
+            timestamp: now,
+            velocity,
+            acceleration: Default::default(),
+            kick: frame
+                .kicked_ball
+                .as_ref()
+                .map(|kick| Self::to_kick(kick, frame, team_color, game_data, now)),
+        };
+        tracked.last_update = now;
+    }
+
+    fn plot(plotter: &Plotter, frame: &TrackedFrame, team_color: &TeamColor, game_data: &GameData) {
+        let factor = game_data.side_factor();
+        if let Some(vel) = frame.balls.first().and_then(|ball| ball.vel.as_ref()) {
+            plotter.plot("tracker/ball/vx", factor * vel.x as f64);
+            plotter.plot("tracker/ball/vy", factor * vel.y as f64);
+        }
+        let allies = frame
+            .robots
+            .iter()
+            .filter(|r| is_ally(&r.robot_id, team_color) == Some(true));
+        for robot in allies {
+            let id = robot.robot_id.id;
+            if let Some(vel) = robot.vel.as_ref() {
+                plotter.plot(format!("tracker/robot{}/vx", id), factor * vel.x as f64);
+                plotter.plot(format!("tracker/robot{}/vy", id), factor * vel.y as f64);
+            }
+            if let Some(vel_angular) = robot.vel_angular {
+                plotter.plot(format!("tracker/robot{}/vangular", id), vel_angular as f64);
+            }
+        }
+    }
+}
+
+impl PreFilter for TrackerFilter {
+    fn step(
+        &mut self,
+        inbound_data: &InboundData,
+        team_color: &TeamColor,
+        filter_data: &mut FilterData,
+    ) {
+        let now = self.clock.now();
+        for packet in &inbound_data.tracker_packet {
+            let source = self.source.get_or_insert_with(|| {
+                info!(
+                    "Following the tracker {} ({})",
+                    packet.source_name.as_deref().unwrap_or("unnamed"),
+                    packet.uuid
+                );
+                packet.uuid.clone()
+            });
+            if *source != packet.uuid {
+                continue;
+            }
+            let Some(frame) = packet.tracked_frame.as_ref() else {
+                continue;
+            };
+
+            let game_data = &filter_data.game_data;
+            if !self.fill {
+                Self::plot(&filter_data.plotter, frame, team_color, game_data);
+                continue;
+            }
+
+            let robots = frame.robots.iter().filter(|r| {
+                let valid = r.robot_id.id <= MAX_ID_ROBOTS as u32;
+                if !valid {
+                    warn!("invalid id");
+                }
+                valid
+            });
+            let (allies, enemies): (Vec<_>, Vec<_>) = robots
+                .filter_map(|r| is_ally(&r.robot_id, team_color).map(|ally| (ally, r)))
+                .partition(|(ally, _)| *ally);
+            Self::fill_robots(
+                &mut filter_data.allies,
+                allies.into_iter().map(|(_, r)| r),
+                game_data,
+                now,
+            );
+            Self::fill_robots(
+                &mut filter_data.enemies,
+                enemies.into_iter().map(|(_, r)| r),
+                game_data,
+                now,
+            );
+            Self::fill_ball(&mut filter_data.ball, frame, team_color, game_data, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::clock::{Clock, ManualClock};
+    use crabe_protocol::protobuf::tracked_vision_packet::{TrackedBall, TrackerWrapperPacket};
+    use std::sync::Arc;
+
+    fn robot(id: u32, color: TrackedTeamColor, x: f32) -> TrackedFrameRobot {
+        TrackedFrameRobot {
+            robot_id: RobotId {
+                id,
+                team_color: color as i32,
+            },
+            pos: Vector2 { x, y: 1.0 },
+            orientation: 0.0,
+            vel: Some(Vector2 { x: 1.0, y: 0.0 }),
+            vel_angular: Some(0.5),
+            visibility: None,
+        }
+    }
+
+    #[test]
+    fn tracked_frames_fill_the_world_frame() {
+        let clock = Arc::new(ManualClock::default());
+        let mut filter = TrackerFilter::new(clock.clone(), true);
+        let mut game_data = GameData::new(TeamColor::Blue);
+        // Our team plays on the positive half, the raw coordinates are mirrored.
+        game_data.positive_half = TeamColor::Blue;
+        let mut filter_data = FilterData {
+            allies: Default::default(),
+            enemies: Default::default(),
+            ball: Default::default(),
+            geometry: Default::default(),
+            game_data,
+            feedback: Default::default(),
+            plotter: Plotter::new(clock.clone()),
+        };
+        let frame = TrackedFrame {
+            frame_number: 1,
+            timestamp: 100.0,
+            balls: vec![TrackedBall {
+                pos: crabe_protocol::protobuf::tracked_vision_packet::Vector3 {
+                    x: 2.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                vel: None,
+                visibility: None,
+            }],
+            robots: vec![
+                robot(3, TrackedTeamColor::Blue, 1.0),
+                robot(4, TrackedTeamColor::Yellow, -1.0),
+            ],
+            kicked_ball: Some(KickedBall {
+                pos: Vector2 { x: 1.0, y: 1.0 },
+                vel: crabe_protocol::protobuf::tracked_vision_packet::Vector3 {
+                    x: 4.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                start_timestamp: 99.5,
+                stop_timestamp: None,
+                stop_pos: None,
+                robot_id: Some(RobotId {
+                    id: 3,
+                    team_color: TrackedTeamColor::Blue as i32,
+                }),
+            }),
+            capabilities: vec![],
+        };
+        let inbound_data = InboundData {
+            tracker_packet: vec![TrackerWrapperPacket {
+                uuid: "tracker".to_string(),
+                source_name: None,
+                tracked_frame: Some(frame),
+            }],
+            ..Default::default()
+        };
+
+        filter.step(&inbound_data, &TeamColor::Blue, &mut filter_data);
+
+        let ally = &filter_data.allies[&3].data;
+        assert_eq!(ally.pose.position, Point2::new(-1.0, -1.0));
+        assert_eq!(ally.velocity.linear.x, -1.0);
+        assert_eq!(ally.velocity.angular, 0.5);
+        assert_eq!(filter_data.enemies[&4].data.pose.position.x, 1.0);
+
+        let ball = &filter_data.ball.data;
+        assert_eq!(ball.position.x, -2.0);
+        let kick = ball.kick.as_ref().unwrap();
+        assert_eq!(kick.kicker, Some(Kicker::Ally(3)));
+        assert_eq!(kick.velocity.x, -4.0);
+        assert_eq!(kick.start, clock.now() - Duration::milliseconds(500));
+    }
+}
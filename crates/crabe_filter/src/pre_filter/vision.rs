@@ -1,19 +1,20 @@
 use crate::data::FilterData;
 use crate::PreFilter;
 
+use crabe_framework::clock::SharedClock;
 use crabe_framework::data::input::InboundData;
 use crabe_framework::data::world::TeamColor;
 
 mod detection {
     use crate::data::{FilterData, FrameInfo};
-    use chrono::{DateTime, LocalResult, TimeZone, Utc};
+    use chrono::{DateTime, Utc};
     use crabe_framework::data::world::TeamColor;
     use crabe_protocol::protobuf::vision_packet::SslDetectionFrame;
-    use log::error;
 
     mod robot {
         use crate::data::{camera::CamRobot, FrameInfo, TrackedRobot, TrackedRobotMap};
         use crabe_framework::constant::MAX_ID_ROBOTS;
+        use crabe_framework::data::world::GameData;
         use crabe_framework::data::world::{AllyInfo, EnemyInfo, Robot, TeamColor};
         use crabe_protocol::protobuf::vision_packet::SslDetectionRobot;
         use log::warn;
@@ -48,6 +49,7 @@ mod detection {
             detection: &mut RobotDetectionInfo,
             frame: &FrameInfo,
             team_color: &TeamColor,
+            game_data: &GameData,
         ) {
             let map_packet = |r: &SslDetectionRobot| {
                 r.robot_id.and_then(|id| {
@@ -58,8 +60,12 @@ mod detection {
                         Some(CamRobot {
                             id: id as u8,
                             frame_info: frame.clone(),
-                            position: Point2::new(r.x as f64 / 1000.0, r.y as f64 / 1000.0),
-                            orientation: r.orientation.unwrap_or(0.0) as f64,
+                            position: game_data.mirror_point(Point2::new(
+                                r.x as f64 / 1000.0,
+                                r.y as f64 / 1000.0,
+                            )),
+                            orientation: game_data
+                                .mirror_orientation(r.orientation.unwrap_or(0.0) as f64),
                             confidence: r.confidence as f64,
                         })
                     }
@@ -91,6 +97,7 @@ mod detection {
 
     mod ball {
         use crate::data::{camera::CamBall, FrameInfo, TrackedBall};
+        use crabe_framework::data::world::GameData;
         use crabe_protocol::protobuf::vision_packet::SslDetectionBall;
         use nalgebra::Point3;
 
@@ -99,33 +106,25 @@ mod detection {
             pub tracked: &'a mut TrackedBall,
         }
 
-        pub fn detect_balls(detection: &mut BallDetectionInfo, frame: &FrameInfo) {
+        pub fn detect_balls(
+            detection: &mut BallDetectionInfo,
+            frame: &FrameInfo,
+            game_data: &GameData,
+        ) {
+            let factor = game_data.side_factor();
             let ball_packets = detection.detected.iter().map(|b| CamBall {
                 frame_info: frame.clone(),
                 position: Point3::new(
-                    b.x as f64 / 1000.0,
-                    b.y as f64 / 1000.0,
+                    factor * b.x as f64 / 1000.0,
+                    factor * b.y as f64 / 1000.0,
                     b.z.unwrap_or(0.0) as f64 / 1000.0,
                 ),
                 confidence: b.confidence as f64,
             });
 
             detection.tracked.packets.extend(ball_packets);
-        }
-    }
-
-    fn create_date_time(t_capture: f64) -> DateTime<Utc> {
-        match Utc.timestamp_opt((t_capture) as i64, 0) {
-            LocalResult::Single(dt) => dt,
-            LocalResult::None => {
-                let now_utc = Utc::now();
-                error!("Invalid timestamp, using current time: {}", now_utc);
-                now_utc
-            }
-            LocalResult::Ambiguous(dt_min, dt_max) => {
-                let dt_midpoint = dt_min + (dt_max - dt_min) / 2;
-                error!("Ambiguous timestamp resolved to midpoint: {}", dt_midpoint);
-                dt_midpoint
+            if !detection.detected.is_empty() {
+                detection.tracked.last_update = frame.t_capture;
             }
         }
     }
@@ -134,11 +133,14 @@ mod detection {
         detection: &SslDetectionFrame,
         filter_data: &mut FilterData,
         team_color: &TeamColor,
+        now: DateTime<Utc>,
     ) {
+        // The frames are timestamped on reception, the clock of the vision computer
+        // may not be synchronized with ours.
         let frame_info = FrameInfo {
             camera_id: detection.camera_id,
             frame_number: detection.frame_number,
-            t_capture: create_date_time(Utc::now().timestamp() as f64),
+            t_capture: now,
         };
 
         let mut robot_detection_info = robot::RobotDetectionInfo {
@@ -148,14 +150,23 @@ mod detection {
             tracked_enemies: &mut filter_data.enemies,
         };
 
-        robot::detect_robots(&mut robot_detection_info, &frame_info, team_color);
+        robot::detect_robots(
+            &mut robot_detection_info,
+            &frame_info,
+            team_color,
+            &filter_data.game_data,
+        );
 
         let mut ball_detection_info = ball::BallDetectionInfo {
             detected: &detection.balls,
             tracked: &mut filter_data.ball,
         };
 
-        ball::detect_balls(&mut ball_detection_info, &frame_info)
+        ball::detect_balls(
+            &mut ball_detection_info,
+            &frame_info,
+            &filter_data.game_data,
+        )
     }
 }
 
@@ -167,8 +178,12 @@ mod geometry {
     use crabe_protocol::protobuf::vision_packet::SslGeometryData;
     use nalgebra::Point2;
     use std::collections::HashMap;
+    use std::f64::consts::PI;
 
     pub fn handle_geometry(geometry: &SslGeometryData, filter_data: &mut FilterData) {
+        let game_data = &filter_data.game_data;
+        // Mirroring an arc turns it by half a turn, its angles are not wrapped to keep start < end.
+        let arc_offset = if game_data.is_mirrored() { PI } else { 0.0 };
         let mut cam_geometry = CamGeometry {
             field_length: geometry.field.field_length as f64 / 1000.0,
             field_width: geometry.field.field_width as f64 / 1000.0,
@@ -199,8 +214,14 @@ mod geometry {
                 CamFieldLine {
                     thickness: line.thickness as f64 / 1000.0,
                     line: Line {
-                        start: Point2::new(line.p1.x as f64 / 1000.0, line.p1.y as f64 / 1000.0),
-                        end: Point2::new(line.p2.x as f64 / 1000.0, line.p2.y as f64 / 1000.0),
+                        start: game_data.mirror_point(Point2::new(
+                            line.p1.x as f64 / 1000.0,
+                            line.p1.y as f64 / 1000.0,
+                        )),
+                        end: game_data.mirror_point(Point2::new(
+                            line.p2.x as f64 / 1000.0,
+                            line.p2.y as f64 / 1000.0,
+                        )),
                     },
                 },
             );
@@ -212,13 +233,13 @@ mod geometry {
                 CamFieldArc {
                     thickness: arc.thickness as f64 / 1000.0,
                     arc: Arc {
-                        center: Point2::new(
+                        center: game_data.mirror_point(Point2::new(
                             arc.center.x as f64 / 1000.0,
                             arc.center.y as f64 / 1000.0,
-                        ),
+                        )),
                         radius: arc.radius as f64 / 1000.0,
-                        start: arc.a1 as f64,
-                        end: arc.a2 as f64,
+                        start: arc.a1 as f64 + arc_offset,
+                        end: arc.a2 as f64 + arc_offset,
                     },
                 },
             );
@@ -228,11 +249,15 @@ mod geometry {
     }
 }
 
-pub struct VisionFilter;
+pub struct VisionFilter {
+    clock: SharedClock,
+    /// Whether the detections are tracked, otherwise the vision only gives the geometry.
+    detection: bool,
+}
 
 impl VisionFilter {
-    pub fn new() -> VisionFilter {
-        VisionFilter
+    pub fn new(clock: SharedClock, detection: bool) -> VisionFilter {
+        VisionFilter { clock, detection }
     }
 }
 
@@ -243,9 +268,10 @@ impl PreFilter for VisionFilter {
         team_color: &TeamColor,
         filter_data: &mut FilterData,
     ) {
+        let now = self.clock.now();
         inbound_data.vision_packet.iter().for_each(|packet| {
-            if let Some(detection) = packet.detection.as_ref() {
-                detection::handle_detection(detection, filter_data, team_color);
+            if let Some(detection) = packet.detection.as_ref().filter(|_| self.detection) {
+                detection::handle_detection(detection, filter_data, team_color, now);
             }
 
             if let Some(geometry) = packet.geometry.as_ref() {
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The `Clock` trait is the source of time of the components. Reading the time through a clock
+/// instead of the system time allows replays, unit tests and headless simulations to run the
+/// system deterministically, and faster than real time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The `SharedClock` type is the clock shared by all the components of the system.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The `WallClock` struct is a `Clock` that reads the system time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WallClock;
+
+impl Clock for WallClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// The `ManualClock` struct is a `Clock` whose time only changes when it is set or advanced.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl ManualClock {
+    /// Creates a new `ManualClock` starting at the given time.
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+
+    /// Sets the current time of the clock.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().expect("manual clock poisoned") = now;
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let duration = chrono::Duration::from_std(duration).expect("duration out of range");
+        *self.now.lock().expect("manual clock poisoned") += duration;
+    }
+}
+
+impl Default for ManualClock {
+    /// Creates a `ManualClock` starting at the Unix epoch.
+    fn default() -> Self {
+        Self::new(DateTime::<Utc>::default())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("manual clock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_only_moves_when_advanced() {
+        let clock = ManualClock::default();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+        clock.advance(Duration::from_millis(16));
+        assert_eq!(clock.now() - start, chrono::Duration::milliseconds(16));
+    }
+}
@@ -49,7 +49,13 @@ pub trait FilterComponent: Component {
 /// The `DecisionComponent` trait defines the methods required for a component that makes decisions
 /// for a SSL robot fleet based on the filtered input data.
 pub trait DecisionComponent: Component {
-    fn step(&mut self, data: &World) -> (CommandMap, ToolData);
+    /// Computes the commands of the robots. The `tool_data` is kept from one step to another,
+    /// its annotations persist until they expire or are cleared.
+    fn step(&mut self, data: &World, tool_data: &mut ToolData) -> CommandMap;
+
+    /// Applies the commands sent by the tools during the last iteration (switching the manager,
+    /// the strategies, ...). They take effect from the next call to `step`.
+    fn apply_tool_commands(&mut self, _tool_commands: &ToolCommands) {}
 }
 
 /// The `ToolComponent` trait defines the methods required for a component that manages and
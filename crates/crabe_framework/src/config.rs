@@ -1,3 +1,4 @@
+use crate::robot_profile::RobotProfile;
 use clap::Args;
 
 /// A struct representing some options that are common to multiple CRAbE crates.
@@ -9,4 +10,20 @@ pub struct CommonConfig {
     /// Whether robots are operating in the real world or in simulation.
     #[arg(short, long)]
     pub real: bool,
+    /// Whether the simulator is stepped by one period at each iteration of the system, through
+    /// its synchronous API, instead of running on its own (use with `--manual-clock` to run
+    /// deterministically and faster than real time).
+    #[arg(long, conflicts_with = "real")]
+    pub synchronous: bool,
+    /// TOML file describing the hardware of our robots (see `robots/namec.toml`), our robots
+    /// are used by default.
+    #[arg(long, value_name = "FILE", value_parser = RobotProfile::load)]
+    pub robot_profile: Option<RobotProfile>,
+}
+
+impl CommonConfig {
+    /// Returns the profile of our robots.
+    pub fn robot_profile(&self) -> RobotProfile {
+        self.robot_profile.clone().unwrap_or_default()
+    }
 }
@@ -8,6 +8,15 @@ pub mod geometry;
 pub mod input;
 /// The output module contains the output struct of the robot's control system.
 pub mod output;
+/// The plot module contains the named time series that the components send to the tools,
+/// to tune and check them.
+pub mod plot;
+/// The referee module contains the state of the match sent by the game controller,
+/// such as the current stage, command and game events.
+pub mod referee;
+/// The timing module contains the execution time measurements of the system loop,
+/// used to check that each component fits in the control period.
+pub mod timing;
 /// The tool module contains the tool struct of the robot's control system,
 /// such as an annotation and a graph.
 pub mod tool;
@@ -1,28 +1,252 @@
+use chrono::{DateTime, Utc};
 use crabe_math::shape::{Circle, Line, Rectangle};
 use nalgebra::Point2;
 use serde::Serialize;
 use serde_with::serde_as;
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// The layer of the annotations added without a layer.
+pub const DEFAULT_LAYER: &str = "default";
+
+/// The `Lifetime` enum tells how long an annotation stays in its store.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Lifetime {
+    /// The annotation stays until it is removed or its store is cleared.
+    #[default]
+    Persistent,
+    /// The annotation is displayed during the given number of frames (at least one).
+    Frames(u32),
+    /// The annotation is displayed during the given duration.
+    Duration(Duration),
+}
+
+/// When an annotation of the store expires.
+#[derive(Clone, Copy, Debug)]
+enum Expiry {
+    Never,
+    /// The number of frames left before the annotation is removed.
+    Frames(u32),
+    At(DateTime<Utc>),
+}
+
+#[derive(Clone, Serialize)]
+struct Entry {
+    #[serde(flatten)]
+    annotation: Annotation,
+    #[serde(skip)]
+    expiry: Expiry,
+}
 
 #[serde_as]
 #[derive(Clone, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 /// A structure for storing and managing named annotations to be drawn on the SSL RoboCup field viewer.
+///
+/// The annotations persist from one step to another, unless they are given a `Lifetime`, in
+/// which case they are removed by `tick` once expired.
 pub struct AnnotationStore {
     #[serde_as(as = "Vec<(_, _)>")]
-    annotations: HashMap<String, Annotation>,
+    annotations: HashMap<String, Entry>,
+    /// The lifetime given to the annotations added without one.
+    #[serde(skip)]
+    lifetime: Lifetime,
+    /// The time of the last tick.
+    #[serde(skip)]
+    now: DateTime<Utc>,
 }
-/// An enumeration representing various annotation types that can be displayed in the SSL RoboCup field viewer.
-#[derive(Clone, Serialize)]
+
+/// A text label drawn on the field.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Text {
+    /// The position of the bottom-left corner of the text, in meters.
+    pub position: Point2<f64>,
+    /// The text to display.
+    pub text: String,
+    /// The height of the text, in meters.
+    pub size: f64,
+}
+
+/// An enumeration representing the shapes that can be displayed in the SSL RoboCup field viewer.
+#[derive(Clone, Debug, Serialize)]
 #[serde(tag = "kind", content = "content", rename_all = "camelCase")]
-pub enum Annotation {
+pub enum Shape {
     Circle(Circle),
     Line(Line),
     Rectangle(Rectangle),
     Point(Point2<f64>),
+    Text(Text),
+    /// An open path going through the points, in order.
+    Polyline(Vec<Point2<f64>>),
+    /// A closed path going through the points, in order.
+    Polygon(Vec<Point2<f64>>),
+    /// A line with an arrow head on its end.
+    Arrow(Line),
+}
+
+/// The `Style` struct describes how the viewer draws an annotation.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
+    /// The colour of the stroke, as a CSS colour (e.g. `"red"` or `"#ff0000"`).
+    pub color: String,
+    /// The width of the stroke, in pixels.
+    pub width: f64,
+    /// The colour of the inside of closed shapes, as a CSS colour, if they are filled.
+    pub fill: Option<String>,
+    /// Whether the stroke is dashed.
+    pub dashed: bool,
+}
+
+impl Style {
+    /// Creates a plain style with the given stroke colour.
+    pub fn new(color: impl Into<String>) -> Self {
+        Self {
+            color: color.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the width of the stroke, in pixels.
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Fills the closed shapes with the given colour.
+    pub fn fill(mut self, fill: impl Into<String>) -> Self {
+        self.fill = Some(fill.into());
+        self
+    }
+
+    /// Dashes the stroke.
+    pub fn dashed(mut self) -> Self {
+        self.dashed = true;
+        self
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            color: "black".to_string(),
+            width: 1.0,
+            fill: None,
+            dashed: false,
+        }
+    }
+}
+
+/// An annotation to be displayed in the SSL RoboCup field viewer: a shape, its style and the
+/// layer it belongs to, so that the viewer can show or hide groups of annotations.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    #[serde(flatten)]
+    pub shape: Shape,
+    pub style: Style,
+    pub layer: String,
+}
+
+impl Annotation {
+    /// Creates an annotation of the default layer, with the default style.
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            style: Default::default(),
+            layer: DEFAULT_LAYER.to_string(),
+        }
+    }
+
+    /// Sets the style of the annotation.
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Moves the annotation to the layer `layer` (e.g. `"navigation"` or `"pass"`).
+    pub fn with_layer(mut self, layer: impl Into<String>) -> Self {
+        self.layer = layer.into();
+        self
+    }
+}
+
+impl From<Shape> for Annotation {
+    fn from(shape: Shape) -> Self {
+        Self::new(shape)
+    }
 }
 
 impl AnnotationStore {
+    /// Creates an empty store whose annotations expire relative to the time `now`.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the lifetime given to the annotations added without one, the annotations already in
+    /// the store keep their lifetime.
+    pub fn set_lifetime(&mut self, lifetime: Lifetime) {
+        self.lifetime = lifetime;
+    }
+
+    /// Removes the expired annotations. It is called once per frame, before the annotations of
+    /// the frame are added.
+    ///
+    /// # Arguments
+    ///
+    /// * `now`: The time of the new frame.
+    pub fn tick(&mut self, now: DateTime<Utc>) {
+        self.now = now;
+        self.annotations.retain(|_, entry| match &mut entry.expiry {
+            Expiry::Never => true,
+            Expiry::Frames(frames) => {
+                *frames = frames.saturating_sub(1);
+                *frames > 0
+            }
+            Expiry::At(deadline) => *deadline > now,
+        });
+    }
+
+    /// Add an annotation to be displayed in the field viewer, replacing any annotation with the
+    /// same identifier. It gets the lifetime of the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `annotation`: The annotation, or a shape to draw with the default style and layer.
+    pub fn add(&mut self, id: String, annotation: impl Into<Annotation>) {
+        self.add_for(id, annotation, self.lifetime);
+    }
+
+    /// Add an annotation to be displayed in the field viewer during `lifetime`, replacing any
+    /// annotation with the same identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `annotation`: The annotation, or a shape to draw with the default style and layer.
+    /// * `lifetime`: How long the annotation stays in the store.
+    pub fn add_for(&mut self, id: String, annotation: impl Into<Annotation>, lifetime: Lifetime) {
+        let expiry = match lifetime {
+            Lifetime::Persistent => Expiry::Never,
+            Lifetime::Frames(frames) => Expiry::Frames(frames.max(1)),
+            Lifetime::Duration(duration) => Expiry::At(
+                self.now + chrono::Duration::from_std(duration).expect("duration out of range"),
+            ),
+        };
+        self.annotations.insert(
+            id,
+            Entry {
+                annotation: annotation.into(),
+                expiry,
+            },
+        );
+    }
+
     /// Add a circle annotation to be displayed in the field viewer.
     ///
     /// # Arguments
@@ -30,7 +254,7 @@ impl AnnotationStore {
     /// * `id`: A unique identifier for the annotation.
     /// * `circle`: The circle shape to be added as an annotation.
     pub fn add_circle(&mut self, id: String, circle: Circle) {
-        self.annotations.insert(id, Annotation::Circle(circle));
+        self.add(id, Shape::Circle(circle));
     }
 
     /// Add a point annotation to be displayed in the field viewer.
@@ -40,7 +264,7 @@ impl AnnotationStore {
     /// * `id`: A unique identifier for the annotation.
     /// * `point`: The 2D point to be added as an annotation.
     pub fn add_point(&mut self, id: String, point: Point2<f64>) {
-        self.annotations.insert(id, Annotation::Point(point));
+        self.add(id, Shape::Point(point));
     }
 
     /// Add a rectangle annotation to be displayed in the field viewer.
@@ -50,8 +274,57 @@ impl AnnotationStore {
     /// * `id`: A unique identifier for the annotation.
     /// * `rectangle`: The rectangle shape to be added as an annotation.
     pub fn add_rectangle(&mut self, id: String, rectangle: Rectangle) {
-        self.annotations
-            .insert(id, Annotation::Rectangle(rectangle));
+        self.add(id, Shape::Rectangle(rectangle));
+    }
+
+    /// Add a line annotation to be displayed in the field viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `line`: The line segment to be added as an annotation.
+    pub fn add_line(&mut self, id: String, line: Line) {
+        self.add(id, Shape::Line(line));
+    }
+
+    /// Add an arrow annotation to be displayed in the field viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `line`: The line segment of the arrow, the head being drawn on its end.
+    pub fn add_arrow(&mut self, id: String, line: Line) {
+        self.add(id, Shape::Arrow(line));
+    }
+
+    /// Add a text annotation to be displayed in the field viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `text`: The text label to be added as an annotation.
+    pub fn add_text(&mut self, id: String, text: Text) {
+        self.add(id, Shape::Text(text));
+    }
+
+    /// Add a polyline annotation to be displayed in the field viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `points`: The points of the open path, in order.
+    pub fn add_polyline(&mut self, id: String, points: Vec<Point2<f64>>) {
+        self.add(id, Shape::Polyline(points));
+    }
+
+    /// Add a polygon annotation to be displayed in the field viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: A unique identifier for the annotation.
+    /// * `points`: The vertices of the polygon, in order.
+    pub fn add_polygon(&mut self, id: String, points: Vec<Point2<f64>>) {
+        self.add(id, Shape::Polygon(points));
     }
 
     /// Remove all annotations from the store.
@@ -59,6 +332,16 @@ impl AnnotationStore {
         self.annotations.clear();
     }
 
+    /// Remove all annotations of a layer from the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer`: The layer of the annotations to be removed.
+    pub fn clear_layer(&mut self, layer: &str) {
+        self.annotations
+            .retain(|_, entry| entry.annotation.layer != layer);
+    }
+
     /// Remove an annotation from the store by its unique identifier.
     ///
     /// # Arguments
@@ -78,6 +361,74 @@ impl AnnotationStore {
     ///
     /// An `Option<&Annotation>` containing a reference to the annotation if it exists, or `None` if not found.
     pub fn get(&self, id: &str) -> Option<&Annotation> {
-        self.annotations.get(id)
+        self.annotations.get(id).map(|entry| &entry.annotation)
+    }
+
+    /// Returns whether the store has no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn annotations_are_serialized_with_their_style_and_layer() {
+        let mut store = AnnotationStore::default();
+        store.add(
+            "path".to_string(),
+            Annotation::new(Shape::Polyline(vec![
+                Point2::new(0.0, 0.0),
+                Point2::new(1.0, 0.5),
+            ]))
+            .with_style(Style::new("red").width(2.0).dashed())
+            .with_layer("navigation"),
+        );
+        store.add_point("target".to_string(), Point2::new(1.0, 0.5));
+
+        let value = serde_json::to_value(&store.annotations["path"]).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "kind": "polyline",
+                "content": [[0.0, 0.0], [1.0, 0.5]],
+                "style": {"color": "red", "width": 2.0, "fill": null, "dashed": true},
+                "layer": "navigation",
+            })
+        );
+
+        store.clear_layer("navigation");
+        assert!(store.get("path").is_none());
+        assert_eq!(store.get("target").unwrap().layer, DEFAULT_LAYER);
+    }
+
+    #[test]
+    fn annotations_expire_after_their_lifetime() {
+        let start = DateTime::<Utc>::default();
+        let mut store = AnnotationStore::new(start);
+        store.set_lifetime(Lifetime::Frames(2));
+        store.add_point("frames".to_string(), Point2::origin());
+        store.add_for(
+            "duration".to_string(),
+            Shape::Point(Point2::origin()),
+            Lifetime::Duration(Duration::from_millis(100)),
+        );
+        store.add_for(
+            "persistent".to_string(),
+            Shape::Point(Point2::origin()),
+            Lifetime::Persistent,
+        );
+
+        store.tick(start + chrono::Duration::milliseconds(16));
+        assert!(store.get("frames").is_some());
+        store.tick(start + chrono::Duration::milliseconds(32));
+        assert!(store.get("frames").is_none());
+        assert!(store.get("duration").is_some());
+        store.tick(start + chrono::Duration::milliseconds(100));
+        assert!(store.get("duration").is_none());
+        assert!(store.get("persistent").is_some());
     }
 }
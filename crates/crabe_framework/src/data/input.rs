@@ -1,5 +1,6 @@
 use crate::data::output::FeedbackMap;
 use crabe_protocol::protobuf::game_controller_packet::Referee;
+use crabe_protocol::protobuf::tracked_vision_packet::TrackerWrapperPacket;
 use crabe_protocol::protobuf::vision_packet::SslWrapperPacket;
 use std::fmt::Debug;
 
@@ -13,6 +14,9 @@ pub struct InboundData {
     /// Game controller packet received by the software. This can come from
     /// SSL-Game-Controller.
     pub gc_packet: Vec<Referee>,
+    /// Tracked frames received by the software. These come from an external
+    /// tracker, such as the one of an auto-referee.
+    pub tracker_packet: Vec<TrackerWrapperPacket>,
     /// Simulator or USB Packet that provides feedback from the robot,
     /// such as odometry or infrared data.
     pub feedback: FeedbackMap,
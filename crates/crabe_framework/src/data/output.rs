@@ -6,17 +6,18 @@ use std::collections::HashMap;
 pub type FeedbackMap = HashMap<u32, Feedback>;
 
 /// The Feedback struct contains information about the feedback data for a robot in the game.
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Feedback {
     /// A boolean value indicating whether the robot has possession of the ball.
     pub has_ball: bool,
-    /// The current voltage level of the robot (only in real).
-    pub voltage: f32,
+    /// The current voltage level of the robot in volts (only in real).
+    pub voltage: Option<f32>,
+    /// The speed of the four motors of the robot (only in real).
+    pub motor_speeds: Option<[f32; 4]>,
 }
 
 /// The CommandMap type is a hash map that stores commands to be sent to the robots in the game.
 /// Each robot is identified by its ID.
-
 pub type CommandMap = HashMap<u8, Command>;
 
 /// The Kick enum is used to specify the type of kick to be performed by a robot.
@@ -28,6 +29,9 @@ pub enum Kick {
     ChipKick { power: f32 },
 }
 
+/// The Command struct is the order sent to a robot. Its velocities are expressed in the frame of
+/// the robot, so they don't depend on the half of the field our team is on: the simulator and the
+/// real robots don't need to convert them back from the `World` frame.
 #[derive(Copy, Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Command {
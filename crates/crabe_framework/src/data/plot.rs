@@ -0,0 +1,70 @@
+use crate::clock::SharedClock;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// The `PlotSample` struct is a value of a named time series sent to the tools.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlotSample {
+    /// The name of the series, using `/` to group them (e.g. `"robot3/vx_cmd"`).
+    pub name: String,
+    /// The time of the value.
+    pub timestamp: DateTime<Utc>,
+    /// The value.
+    pub value: f64,
+}
+
+/// The `Plotter` struct collects the samples of the components that don't have access to the
+/// `ToolData` (filters, guards). It is a handle that can be cloned and shared, the samples are
+/// moved into the `ToolData` once per iteration of the system loop.
+#[derive(Clone)]
+pub struct Plotter {
+    clock: SharedClock,
+    samples: Arc<Mutex<Vec<PlotSample>>>,
+}
+
+impl Plotter {
+    /// Creates a new `Plotter` timestamping the samples with `clock`.
+    pub fn new(clock: SharedClock) -> Self {
+        Self {
+            clock,
+            samples: Default::default(),
+        }
+    }
+
+    /// Adds the value `value` to the series `name`, at the current time.
+    pub fn plot(&self, name: impl Into<String>, value: f64) {
+        let sample = PlotSample {
+            name: name.into(),
+            timestamp: self.clock.now(),
+            value,
+        };
+        self.samples.lock().expect("plotter poisoned").push(sample);
+    }
+
+    /// Removes and returns the samples added since the last call.
+    pub fn take(&self) -> Vec<PlotSample> {
+        std::mem::take(&mut *self.samples.lock().expect("plotter poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn samples_are_shared_between_handles() {
+        let plotter = Plotter::new(Arc::new(ManualClock::default()));
+        let handle = plotter.clone();
+        handle.plot("robot3/vx_cmd", 0.5);
+        plotter.plot("robot3/vx", 0.25);
+
+        let samples = plotter.take();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].name, "robot3/vx_cmd");
+        assert_eq!(samples[1].value, 0.25);
+        assert!(handle.take().is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+mod event;
+pub use self::event::{GameEvent, GameEventKind};
+
+mod game_state;
+pub use self::game_state::{GameState, GameStateMachine, Side};
+
+use crate::data::world::TeamColor;
+use chrono::{DateTime, Utc};
+use nalgebra::Point2;
+use serde::Serialize;
+
+/// The `Stage` enum represents the "coarse" stages of a SSL match, as sent by the game controller.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Stage {
+    /// The first half is about to start, a kickoff is called within this stage.
+    #[default]
+    NormalFirstHalfPre,
+    /// The first half of the normal game, before half time.
+    NormalFirstHalf,
+    /// Half time between first and second halves.
+    NormalHalfTime,
+    /// The second half is about to start, a kickoff is called within this stage.
+    NormalSecondHalfPre,
+    /// The second half of the normal game, after half time.
+    NormalSecondHalf,
+    /// The break before extra time.
+    ExtraTimeBreak,
+    /// The first half of extra time is about to start, a kickoff is called within this stage.
+    ExtraFirstHalfPre,
+    /// The first half of extra time.
+    ExtraFirstHalf,
+    /// Half time between first and second extra halves.
+    ExtraHalfTime,
+    /// The second half of extra time is about to start, a kickoff is called within this stage.
+    ExtraSecondHalfPre,
+    /// The second half of extra time.
+    ExtraSecondHalf,
+    /// The break before penalty shootout.
+    PenaltyShootoutBreak,
+    /// The penalty shootout.
+    PenaltyShootout,
+    /// The game is over.
+    PostGame,
+}
+
+/// The `RefereeCommand` enum represents the "fine" states of play sent by the game controller.
+/// Commands that concern only one team carry the color of this team.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(tag = "kind", content = "team", rename_all = "camelCase")]
+pub enum RefereeCommand {
+    /// All robots should completely stop moving.
+    #[default]
+    Halt,
+    /// Robots must keep 50 cm from the ball.
+    Stop,
+    /// A prepared kickoff or penalty may now be taken.
+    NormalStart,
+    /// The ball is dropped and free for either team.
+    ForceStart,
+    /// The team may move into kickoff position.
+    PrepareKickoff(TeamColor),
+    /// The team may move into penalty position.
+    PreparePenalty(TeamColor),
+    /// The team may take a direct free kick.
+    DirectFree(TeamColor),
+    /// The team may take an indirect free kick.
+    IndirectFree(TeamColor),
+    /// The team is currently in a timeout.
+    Timeout(TeamColor),
+    /// The team just scored a goal (deprecated by the game controller, use the score instead).
+    Goal(TeamColor),
+    /// Equivalent to `Stop`, but the team must place the ball at the designated position.
+    BallPlacement(TeamColor),
+}
+
+/// The `Referee` struct holds the last state of the match sent by the game controller.
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Referee {
+    /// The current stage of the match.
+    pub stage: Stage,
+    /// The time left in the current stage in seconds, negative if the stage runs over its time.
+    /// Only present in stages with a duration (halves, breaks, ...).
+    pub stage_time_left: Option<f64>,
+    /// The current command of the referee.
+    pub command: RefereeCommand,
+    /// The command that will be issued after the current stoppage and ball placement.
+    pub next_command: Option<RefereeCommand>,
+    /// The number of commands issued since the start of the game controller.
+    pub command_counter: u32,
+    /// The timestamp at which the current command was issued.
+    pub command_timestamp: DateTime<Utc>,
+    /// The position where the ball must be placed in meters, only present during ball placement.
+    pub designated_position: Option<Point2<f64>>,
+    /// The time left in seconds before the current action (free kick, kickoff, ball placement, ...)
+    /// times out. It can be negative.
+    pub action_time_left: Option<f64>,
+    /// The game events that led to the current command.
+    pub game_events: Vec<GameEvent>,
+}
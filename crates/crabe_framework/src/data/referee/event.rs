@@ -0,0 +1,70 @@
+use crate::data::world::TeamColor;
+use chrono::{DateTime, Utc};
+use nalgebra::Point2;
+use serde::Serialize;
+
+/// The `GameEventKind` enum lists the game events that can be raised by the game controller
+/// or the autoRefs.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum GameEventKind {
+    BallLeftFieldTouchLine,
+    BallLeftFieldGoalLine,
+    AimlessKick,
+    AttackerTooCloseToDefenseArea,
+    DefenderInDefenseArea,
+    BoundaryCrossing,
+    KeeperHeldBall,
+    BotDribbledBallTooFar,
+    BotPushedBot,
+    BotHeldBallDeliberately,
+    BotTippedOver,
+    AttackerTouchedBallInDefenseArea,
+    BotKickedBallTooFast,
+    BotCrashUnique,
+    BotCrashDrawn,
+    DefenderTooCloseToKickPoint,
+    BotTooFastInStop,
+    BotInterferedPlacement,
+    PossibleGoal,
+    Goal,
+    InvalidGoal,
+    AttackerDoubleTouchedBall,
+    PlacementSucceeded,
+    PenaltyKickFailed,
+    NoProgressInGame,
+    PlacementFailed,
+    MultipleCards,
+    MultipleFouls,
+    BotSubstitution,
+    TooManyRobots,
+    ChallengeFlag,
+    ChallengeFlagHandled,
+    EmergencyStop,
+    UnsportingBehaviorMinor,
+    UnsportingBehaviorMajor,
+    Prepared,
+    IndirectGoal,
+    ChippedGoal,
+    KickTimeout,
+    AttackerTouchedOpponentInDefenseArea,
+    DefenderInDefenseAreaPartially,
+    MultiplePlacementFailures,
+}
+
+/// The `GameEvent` struct represents an event of the match (foul, goal, ball out of the field, ...)
+/// with the information that is common to most of the events.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GameEvent {
+    /// The kind of the event.
+    pub kind: GameEventKind,
+    /// The team that caused the event, if any.
+    pub by_team: Option<TeamColor>,
+    /// The id of the robot that caused the event, if known.
+    pub by_bot: Option<u8>,
+    /// The location of the event in meters, if known.
+    pub location: Option<Point2<f64>>,
+    /// The timestamp at which the event was created, if known.
+    pub created: Option<DateTime<Utc>>,
+}
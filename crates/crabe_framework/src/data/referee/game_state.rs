@@ -0,0 +1,281 @@
+use crate::data::referee::{Referee, RefereeCommand};
+use crate::data::world::{Ball, TeamColor};
+use chrono::{DateTime, Duration, Utc};
+use nalgebra::Point2;
+use serde::Serialize;
+
+/// The distance in meters the ball must travel after a kick for the ball to be in play.
+pub const BALL_IN_PLAY_DISTANCE: f64 = 0.05;
+/// The time after which a kickoff or a free kick becomes normal play if the ball was not kicked.
+pub const KICK_TIMEOUT_SECS: i64 = 10;
+
+/// The `Side` enum tells which team a `GameState` applies to.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Side {
+    Ally,
+    Enemy,
+}
+
+impl Side {
+    /// Returns the side of the team of the given color, with `ally` the color of our team.
+    pub fn of(color: TeamColor, ally: TeamColor) -> Self {
+        if color == ally {
+            Side::Ally
+        } else {
+            Side::Enemy
+        }
+    }
+}
+
+/// The `GameState` enum is the high-level state of the match derived from the referee commands
+/// and the ball, following the SSL rules.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(tag = "kind", content = "side", rename_all = "camelCase")]
+pub enum GameState {
+    /// Robots must not move (also used during timeouts).
+    #[default]
+    Halt,
+    /// Robots must slow down and keep their distance from the ball.
+    Stop,
+    /// The team moves into kickoff position.
+    PrepareKickoff(Side),
+    /// The team may take the kickoff.
+    Kickoff(Side),
+    /// The game is stopped and the team will take the next free kick.
+    PrepareFreeKick(Side),
+    /// The team may take the free kick.
+    FreeKick(Side),
+    /// The team moves into penalty position.
+    PreparePenalty(Side),
+    /// The team may take the penalty kick.
+    Penalty(Side),
+    /// The team must place the ball at the designated position.
+    BallPlacement(Side),
+    /// Normal play, both teams may touch the ball.
+    Running,
+}
+
+/// The `GameStateMachine` struct derives the `GameState` from the successive referee states.
+/// It keeps track of the previous state and of the ball at the start of a kick to know when
+/// the ball is in play.
+#[derive(Clone, Debug, Default)]
+pub struct GameStateMachine {
+    state: GameState,
+    command_counter: Option<u32>,
+    kick_start: Option<DateTime<Utc>>,
+    kick_ball_position: Option<Point2<f64>>,
+}
+
+impl GameStateMachine {
+    /// Returns the current game state.
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    fn on_command(&self, referee: &Referee, ally: TeamColor) -> GameState {
+        let side = |color| Side::of(color, ally);
+        match referee.command {
+            RefereeCommand::Halt | RefereeCommand::Timeout(_) => GameState::Halt,
+            RefereeCommand::Stop | RefereeCommand::Goal(_) => match referee.next_command {
+                Some(RefereeCommand::DirectFree(color) | RefereeCommand::IndirectFree(color)) => {
+                    GameState::PrepareFreeKick(side(color))
+                }
+                _ => GameState::Stop,
+            },
+            RefereeCommand::BallPlacement(color) => GameState::BallPlacement(side(color)),
+            RefereeCommand::PrepareKickoff(color) => GameState::PrepareKickoff(side(color)),
+            RefereeCommand::PreparePenalty(color) => GameState::PreparePenalty(side(color)),
+            RefereeCommand::DirectFree(color) | RefereeCommand::IndirectFree(color) => {
+                GameState::FreeKick(side(color))
+            }
+            RefereeCommand::NormalStart => match self.state {
+                GameState::PrepareKickoff(s) | GameState::Kickoff(s) => GameState::Kickoff(s),
+                GameState::PreparePenalty(s) | GameState::Penalty(s) => GameState::Penalty(s),
+                _ => GameState::Running,
+            },
+            RefereeCommand::ForceStart => GameState::Running,
+        }
+    }
+
+    /// Returns whether the ball moved by `BALL_IN_PLAY_DISTANCE` since the start of the kick.
+    fn ball_moved(&self, ball: Option<&Ball>) -> bool {
+        ball.zip(self.kick_ball_position)
+            .is_some_and(|(ball, start)| {
+                (ball.position_2d() - start).norm() >= BALL_IN_PLAY_DISTANCE
+            })
+    }
+
+    fn ball_in_play(&self, ball: Option<&Ball>, now: DateTime<Utc>) -> bool {
+        let timed_out = self
+            .kick_start
+            .is_some_and(|start| now - start >= Duration::seconds(KICK_TIMEOUT_SECS));
+
+        timed_out || self.ball_moved(ball)
+    }
+
+    /// Updates the game state with the last referee state and ball, and returns it.
+    ///
+    /// # Arguments
+    ///
+    /// * `referee`: The last state sent by the game controller.
+    /// * `ball`: The ball, if it is visible.
+    /// * `ally`: The color of our team.
+    /// * `now`: The current time.
+    pub fn update(
+        &mut self,
+        referee: &Referee,
+        ball: Option<&Ball>,
+        ally: TeamColor,
+        now: DateTime<Utc>,
+    ) -> GameState {
+        if self.command_counter != Some(referee.command_counter) {
+            self.command_counter = Some(referee.command_counter);
+            self.state = self.on_command(referee, ally);
+            self.kick_start = Some(now);
+            self.kick_ball_position = ball.map(|b| b.position_2d());
+        } else {
+            // The ball may not have been visible when the command was issued.
+            if self.kick_ball_position.is_none() {
+                self.kick_ball_position = ball.map(|b| b.position_2d());
+            }
+
+            match self.state {
+                // The next command can be set by the game controller during the stop.
+                GameState::Stop | GameState::PrepareFreeKick(_) => {
+                    self.state = self.on_command(referee, ally);
+                }
+                GameState::Kickoff(_) | GameState::FreeKick(_) if self.ball_in_play(ball, now) => {
+                    self.state = GameState::Running;
+                }
+                // The attempt ends with a referee command when the ball is not kicked in time.
+                GameState::Penalty(_) if self.ball_moved(ball) => {
+                    self.state = GameState::Running;
+                }
+                _ => {}
+            }
+        }
+
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+
+    fn referee(command: RefereeCommand, counter: u32) -> Referee {
+        Referee {
+            command,
+            command_counter: counter,
+            ..Default::default()
+        }
+    }
+
+    fn ball(x: f64, y: f64) -> Ball {
+        Ball {
+            position: Point3::new(x, y, 0.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn kickoff_is_running_once_the_ball_moved() {
+        let mut machine = GameStateMachine::default();
+        let now = Utc::now();
+        let ally = TeamColor::Blue;
+        let prepare = referee(RefereeCommand::PrepareKickoff(TeamColor::Blue), 1);
+        let start = referee(RefereeCommand::NormalStart, 2);
+
+        let state = machine.update(&prepare, Some(&ball(0.0, 0.0)), ally, now);
+        assert_eq!(state, GameState::PrepareKickoff(Side::Ally));
+        let state = machine.update(&start, Some(&ball(0.0, 0.0)), ally, now);
+        assert_eq!(state, GameState::Kickoff(Side::Ally));
+        let state = machine.update(&start, Some(&ball(0.03, 0.0)), ally, now);
+        assert_eq!(state, GameState::Kickoff(Side::Ally));
+        let state = machine.update(&start, Some(&ball(0.06, 0.0)), ally, now);
+        assert_eq!(state, GameState::Running);
+    }
+
+    #[test]
+    fn free_kick_is_running_after_the_timeout() {
+        let mut machine = GameStateMachine::default();
+        let now = Utc::now();
+        let ally = TeamColor::Yellow;
+        let free_kick = referee(RefereeCommand::DirectFree(TeamColor::Blue), 1);
+
+        let state = machine.update(&free_kick, Some(&ball(1.0, 1.0)), ally, now);
+        assert_eq!(state, GameState::FreeKick(Side::Enemy));
+        let later = now + Duration::seconds(KICK_TIMEOUT_SECS - 1);
+        let state = machine.update(&free_kick, Some(&ball(1.0, 1.0)), ally, later);
+        assert_eq!(state, GameState::FreeKick(Side::Enemy));
+        let later = now + Duration::seconds(KICK_TIMEOUT_SECS);
+        let state = machine.update(&free_kick, Some(&ball(1.0, 1.0)), ally, later);
+        assert_eq!(state, GameState::Running);
+    }
+
+    #[test]
+    fn stop_with_next_free_kick_prepares_it() {
+        let mut machine = GameStateMachine::default();
+        let now = Utc::now();
+        let mut stop = referee(RefereeCommand::Stop, 1);
+
+        let state = machine.update(&stop, None, TeamColor::Blue, now);
+        assert_eq!(state, GameState::Stop);
+        stop.next_command = Some(RefereeCommand::IndirectFree(TeamColor::Blue));
+        let state = machine.update(&stop, None, TeamColor::Blue, now);
+        assert_eq!(state, GameState::PrepareFreeKick(Side::Ally));
+    }
+
+    #[test]
+    fn normal_start_without_preparation_is_running() {
+        let mut machine = GameStateMachine::default();
+        let now = Utc::now();
+        machine.update(
+            &referee(RefereeCommand::Stop, 1),
+            None,
+            TeamColor::Blue,
+            now,
+        );
+        let state = machine.update(
+            &referee(RefereeCommand::NormalStart, 2),
+            None,
+            TeamColor::Blue,
+            now,
+        );
+        assert_eq!(state, GameState::Running);
+    }
+
+    #[test]
+    fn penalty_follows_its_preparation() {
+        let mut machine = GameStateMachine::default();
+        let now = Utc::now();
+        let prepare = referee(RefereeCommand::PreparePenalty(TeamColor::Yellow), 1);
+        machine.update(&prepare, None, TeamColor::Blue, now);
+        let state = machine.update(
+            &referee(RefereeCommand::NormalStart, 2),
+            None,
+            TeamColor::Blue,
+            now,
+        );
+        assert_eq!(state, GameState::Penalty(Side::Enemy));
+    }
+
+    #[test]
+    fn penalty_is_running_once_the_ball_moved() {
+        let mut machine = GameStateMachine::default();
+        let now = Utc::now();
+        let prepare = referee(RefereeCommand::PreparePenalty(TeamColor::Blue), 1);
+        let start = referee(RefereeCommand::NormalStart, 2);
+        machine.update(&prepare, Some(&ball(4.0, 0.0)), TeamColor::Blue, now);
+        machine.update(&start, Some(&ball(4.0, 0.0)), TeamColor::Blue, now);
+
+        let later = now + Duration::seconds(KICK_TIMEOUT_SECS);
+        let state = machine.update(&start, Some(&ball(4.03, 0.0)), TeamColor::Blue, later);
+        assert_eq!(state, GameState::Penalty(Side::Ally));
+
+        let state = machine.update(&start, Some(&ball(4.1, 0.0)), TeamColor::Blue, later);
+        assert_eq!(state, GameState::Running);
+    }
+}
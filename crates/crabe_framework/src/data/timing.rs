@@ -0,0 +1,55 @@
+use serde::Serialize;
+use serde_with::{serde_as, DurationMilliSecondsWithFrac};
+use std::time::Duration;
+
+/// The `StepTimings` struct holds the time spent in the `step` method of each component
+/// during one iteration of the system loop.
+#[serde_as]
+#[derive(Serialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StepTimings {
+    /// Time spent by the input component, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub input: Duration,
+    /// Time spent by the filter component, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub filter: Duration,
+    /// Time spent by the decision component, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub decision: Duration,
+    /// Time spent by the tool component, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub tool: Duration,
+    /// Time spent by the guard component, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub guard: Duration,
+    /// Time spent by the output component, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub output: Duration,
+}
+
+impl StepTimings {
+    /// Returns the time spent by all the components together.
+    pub fn total(&self) -> Duration {
+        self.input + self.filter + self.decision + self.tool + self.guard + self.output
+    }
+}
+
+/// The `LoopTimings` struct describes how the last complete iteration of the system loop
+/// fits in the period given to the scheduler.
+#[serde_as]
+#[derive(Serialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopTimings {
+    /// The period targeted by the scheduler, in milliseconds once serialized.
+    #[serde_as(as = "DurationMilliSecondsWithFrac<f64>")]
+    pub period: Duration,
+    /// The time spent in each component during the last iteration.
+    pub steps: StepTimings,
+    /// Whether the last iteration went past its deadline.
+    pub overrun: bool,
+    /// The number of iterations that went past their deadline since the start.
+    pub overrun_count: u64,
+    /// The number of ticks dropped by the scheduler since the start to catch up with the period.
+    pub skipped_ticks: u64,
+}
@@ -1,14 +1,245 @@
 use crate::data::annotation::AnnotationStore;
-use serde::Serialize;
+use crate::data::plot::PlotSample;
+use crate::data::timing::LoopTimings;
+use crate::data::world::{GameData, TeamColor};
+use chrono::{DateTime, Utc};
+use nalgebra::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::collections::HashMap;
 
 /// The `ToolData` struct is a container for storing additional data that can be sent to
 /// external tools, such as a viewer or joystick handler.
+///
+/// It is kept from one iteration of the system loop to another, so that the annotations persist
+/// until they expire or are cleared.
+#[serde_as]
 #[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ToolData {
-    #[serde(flatten)]
-    pub annotations: AnnotationStore,
+    /// The annotations, grouped by namespace (usually the name of the strategy or the action
+    /// drawing them), so that each one can be cleared without touching the others.
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub annotations: HashMap<String, AnnotationStore>,
+    /// The execution time of the components during the last iteration of the system loop,
+    /// sent as the `metrics` of the tools.
+    #[serde(rename = "metrics")]
+    pub timings: LoopTimings,
+    /// The samples of the time series plotted during the current iteration, sent once.
+    pub plots: Vec<PlotSample>,
+    /// The time of the current iteration.
+    #[serde(skip)]
+    now: DateTime<Utc>,
+    /// The commands sent to the simulator by the strategies during the current iteration.
+    #[serde(skip)]
+    simulator_commands: Vec<SimulatorCommand>,
 }
 
-/// The `ToolCommands` struct is a container for storing commands that are sent to external
-/// tools.
-pub struct ToolCommands;
+impl ToolData {
+    /// Returns the annotation store of the namespace `namespace`, created if needed.
+    pub fn namespace(&mut self, namespace: &str) -> &mut AnnotationStore {
+        let now = self.now;
+        self.annotations
+            .entry(namespace.to_string())
+            .or_insert_with(|| AnnotationStore::new(now))
+    }
+
+    /// Adds the value `value` to the time series `name` (e.g. `"robot3/vx_cmd"`), at the time
+    /// of the current iteration.
+    pub fn plot(&mut self, name: impl Into<String>, value: f64) {
+        self.plots.push(PlotSample {
+            name: name.into(),
+            timestamp: self.now,
+            value,
+        });
+    }
+
+    /// Sends the command `command`, expressed in the `World` frame, to the simulator at the end of
+    /// the iteration. The command is ignored with the real robots.
+    pub fn control_simulator(&mut self, command: SimulatorCommand) {
+        self.simulator_commands.push(command);
+    }
+
+    /// Removes and returns the commands sent to the simulator during the current iteration.
+    pub fn take_simulator_commands(&mut self) -> Vec<SimulatorCommand> {
+        std::mem::take(&mut self.simulator_commands)
+    }
+
+    /// Removes all the annotations of the namespace `namespace`.
+    pub fn clear_namespace(&mut self, namespace: &str) {
+        if let Some(store) = self.annotations.get_mut(namespace) {
+            store.clear();
+        }
+    }
+
+    /// Starts a new iteration at the time `now`, removing the expired annotations.
+    pub fn tick(&mut self, now: DateTime<Utc>) {
+        self.now = now;
+        self.annotations
+            .values_mut()
+            .for_each(|store| store.tick(now));
+    }
+}
+
+/// The `SimulatorCommand` enum lists the commands that the tools can send to the simulator.
+/// The positions and velocities are expressed in the `World` frame, the tool server converts
+/// them to the raw frame of the simulator before they reach the output.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "payload", rename_all = "camelCase")]
+pub enum SimulatorCommand {
+    /// Moves the ball to `position` (in meters) with the velocity `velocity` (in m.s-1).
+    #[serde(rename_all = "camelCase")]
+    TeleportBall {
+        position: Point2<f64>,
+        #[serde(default)]
+        velocity: Vector2<f64>,
+    },
+    /// Moves a robot to `position` (in meters) with the orientation `orientation` (in radians),
+    /// or removes it from the field when `present` is false.
+    #[serde(rename_all = "camelCase")]
+    TeleportRobot {
+        id: u8,
+        color: TeamColor,
+        position: Point2<f64>,
+        orientation: f64,
+        #[serde(default = "present_default")]
+        present: bool,
+    },
+    /// Changes the speed of the simulation, 1.0 being real time.
+    SetSpeed(f64),
+}
+
+fn present_default() -> bool {
+    true
+}
+
+impl SimulatorCommand {
+    /// Converts the command between the `World` frame and the raw frame of the league software.
+    pub fn mirror(self, game_data: &GameData) -> Self {
+        match self {
+            SimulatorCommand::TeleportBall { position, velocity } => {
+                SimulatorCommand::TeleportBall {
+                    position: game_data.mirror_point(position),
+                    velocity: velocity * game_data.side_factor(),
+                }
+            }
+            SimulatorCommand::TeleportRobot {
+                id,
+                color,
+                position,
+                orientation,
+                present,
+            } => SimulatorCommand::TeleportRobot {
+                id,
+                color,
+                position: game_data.mirror_point(position),
+                orientation: game_data.mirror_orientation(orientation),
+                present,
+            },
+            SimulatorCommand::SetSpeed(speed) => SimulatorCommand::SetSpeed(speed),
+        }
+    }
+}
+
+/// The `ToolCommand` enum lists the commands that the tools (viewer, control center, ...) can
+/// send to drive the pipeline.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "payload", rename_all = "camelCase")]
+pub enum ToolCommand {
+    /// Stops running the decision component, the robots only receive the commands of the tools.
+    Pause,
+    /// Runs the decision component again and releases a `HaltAll`.
+    Resume,
+    /// Stops all the robots, whatever the decision component or the tools command, until `Resume`.
+    HaltAll,
+    /// Replaces the manager of the decision component, with the given strategies.
+    #[serde(rename_all = "camelCase")]
+    SwitchManager {
+        manager: String,
+        #[serde(default)]
+        strategies: Vec<String>,
+    },
+    /// Restarts the current manager with the given strategies.
+    SetStrategies(Vec<String>),
+    /// Adds the strategy `name` for each of the robots `robot_ids` to the strategies of the
+    /// current manager.
+    #[serde(rename_all = "camelCase")]
+    StartStrategy { name: String, robot_ids: Vec<u8> },
+    /// Removes the strategy `name` of each of the robots `robot_ids` from the strategies of the
+    /// current manager.
+    #[serde(rename_all = "camelCase")]
+    StopStrategy { name: String, robot_ids: Vec<u8> },
+    /// Adds the guard to the guards applied to the commands, or enables it again.
+    EnableGuard(String),
+    /// Stops applying the guard to the commands.
+    DisableGuard(String),
+    /// Controls the simulator, ignored with the real robots.
+    Simulator(SimulatorCommand),
+}
+
+/// The `ToolCommands` struct holds the commands sent by the tools during the current iteration,
+/// in the order in which they were received.
+#[derive(Clone, Debug, Default)]
+pub struct ToolCommands {
+    commands: Vec<ToolCommand>,
+}
+
+impl ToolCommands {
+    /// Adds a command at the end of the commands.
+    pub fn push(&mut self, command: ToolCommand) {
+        self.commands.push(command);
+    }
+
+    /// Returns an iterator over the commands.
+    pub fn iter(&self) -> std::slice::Iter<'_, ToolCommand> {
+        self.commands.iter()
+    }
+
+    /// Returns whether there are no commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Returns whether the commands contain `command`.
+    pub fn contains(&self, command: &ToolCommand) -> bool {
+        self.commands.contains(command)
+    }
+}
+
+impl<'a> IntoIterator for &'a ToolCommands {
+    type Item = &'a ToolCommand;
+    type IntoIter = std::slice::Iter<'a, ToolCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_commands_are_parsed_from_json() {
+        let command: ToolCommand = serde_json::from_str(r#"{"kind": "haltAll"}"#).unwrap();
+        assert_eq!(command, ToolCommand::HaltAll);
+
+        let command: ToolCommand =
+            serde_json::from_str(r#"{"kind": "disableGuard", "payload": "speed"}"#).unwrap();
+        assert_eq!(command, ToolCommand::DisableGuard("speed".to_string()));
+
+        let command: ToolCommand = serde_json::from_str(
+            r#"{"kind": "simulator", "payload": {
+                "kind": "teleportBall", "payload": {"position": [1.0, -0.5]}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            command,
+            ToolCommand::Simulator(SimulatorCommand::TeleportBall {
+                position: Point2::new(1.0, -0.5),
+                velocity: Vector2::zeros(),
+            })
+        );
+    }
+}
@@ -14,6 +14,36 @@ pub struct Ball {
     pub velocity: Vector3<f64>,
     /// The acceleration of the ball in 3D space in meters per second squared.
     pub acceleration: Vector3<f64>,
+    /// The last kick of the ball while it is still rolling. Only an external tracker detects
+    /// the kicks.
+    pub kick: Option<BallKick>,
+}
+
+/// The `BallKick` struct describes a kick of the ball detected by an external tracker, with its
+/// predicted stop.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BallKick {
+    /// The robot which kicked the ball, if known.
+    pub kicker: Option<Kicker>,
+    /// The position from which the ball was kicked in meters.
+    pub position: Point2<f64>,
+    /// The initial velocity of the ball in meters per second.
+    pub velocity: Vector3<f64>,
+    /// The time at which the ball was kicked.
+    pub start: DateTime<Utc>,
+    /// The predicted time at which the ball stops, if any.
+    pub stop: Option<DateTime<Utc>>,
+    /// The predicted position at which the ball stops in meters, if any.
+    pub stop_position: Option<Point2<f64>>,
+}
+
+/// The `Kicker` enum identifies the robot which kicked the ball.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", content = "id", rename_all = "camelCase")]
+pub enum Kicker {
+    Ally(u8),
+    Enemy(u8),
 }
 
 impl Ball {
@@ -1,7 +1,11 @@
+use crate::data::referee::{GameState, Referee};
 use crate::data::world::{Team, TeamColor};
+use nalgebra::Point2;
 use serde::Serialize;
+use std::f64::consts::PI;
 
-/// The `GameData` struct represents the state of the SSL game, including the teams and which team is on the positive half of the field.
+/// The `GameData` struct represents the state of the SSL game, including the teams, the referee
+/// state and which team is on the positive half of the field.
 #[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GameData {
@@ -11,6 +15,10 @@ pub struct GameData {
     pub enemy: Team,
     /// The color of the team that is on the positive half of the field.
     pub positive_half: TeamColor,
+    /// The last state of the match sent by the game controller.
+    pub referee: Referee,
+    /// The high-level state of the match derived from the referee and the ball.
+    pub state: GameState,
 }
 
 impl GameData {
@@ -20,6 +28,74 @@ impl GameData {
             ally: Team::with_color(team_color),
             enemy: Team::with_color(team_color.opposite()),
             positive_half: team_color.opposite(),
+            referee: Default::default(),
+            state: Default::default(),
         }
     }
+
+    /// Returns whether the raw coordinates of the league software (vision, game controller,
+    /// simulator) are mirrored in the `World`. The `World` always has the ally goal on the
+    /// negative x-axis, so the raw coordinates are mirrored when our team is on the positive half.
+    pub fn is_mirrored(&self) -> bool {
+        self.positive_half == self.ally.color
+    }
+
+    /// Returns the factor to apply to a raw x or y coordinate (or to a raw velocity) to express
+    /// it in the `World`, and the other way around.
+    pub fn side_factor(&self) -> f64 {
+        if self.is_mirrored() {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Converts a raw position into a `World` position, or a `World` position back into a raw one.
+    pub fn mirror_point(&self, point: Point2<f64>) -> Point2<f64> {
+        point * self.side_factor()
+    }
+
+    /// Converts a raw orientation into a `World` orientation, or a `World` orientation back into
+    /// a raw one. The result is in the range `(-PI, PI]`.
+    pub fn mirror_orientation(&self, orientation: f64) -> f64 {
+        if !self.is_mirrored() {
+            return orientation;
+        }
+
+        let mirrored = (orientation + PI).rem_euclid(2.0 * PI);
+        if mirrored > PI {
+            mirrored - 2.0 * PI
+        } else {
+            mirrored
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirroring_is_its_own_inverse() {
+        let mut game_data = GameData::new(TeamColor::Blue);
+        game_data.positive_half = TeamColor::Blue;
+        assert!(game_data.is_mirrored());
+
+        let point = Point2::new(1.5, -0.5);
+        let mirrored = game_data.mirror_point(point);
+        assert_eq!(mirrored, Point2::new(-1.5, 0.5));
+        assert_eq!(game_data.mirror_point(mirrored), point);
+
+        let orientation = 3.0 * PI / 4.0;
+        let mirrored = game_data.mirror_orientation(orientation);
+        assert!((mirrored + PI / 4.0).abs() < 1e-9);
+        assert!((game_data.mirror_orientation(mirrored) - orientation).abs() < 1e-9);
+    }
+
+    #[test]
+    fn default_side_is_not_mirrored() {
+        let game_data = GameData::new(TeamColor::Yellow);
+        assert!(!game_data.is_mirrored());
+        assert_eq!(game_data.mirror_orientation(1.0), 1.0);
+    }
 }
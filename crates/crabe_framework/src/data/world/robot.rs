@@ -3,15 +3,38 @@ use nalgebra::{Point2, Vector2};
 use serde::Serialize;
 use std::collections::HashMap;
 
-/// The `AllyInfo` struct represents the information related to allies in the game.
+/// The `AllyInfo` struct represents the information related to allies in the game,
+/// mostly coming from the feedback sent by the robots.
 #[derive(Serialize, Clone, Default, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct AllyInfo;
+pub struct AllyInfo {
+    /// Whether the robot detects the ball in its dribbler (infrared barrier in real,
+    /// dribbler contact in simulation).
+    pub has_ball: bool,
+    /// The voltage of the battery in volts, only sent by the real robots.
+    pub voltage: Option<f32>,
+    /// The speed of the four motors, only sent by the real robots.
+    pub motor_speeds: Option<[f32; 4]>,
+    /// The time at which the last feedback of the robot was received, if any.
+    pub last_feedback: Option<DateTime<Utc>>,
+}
 
-/// The `EnemyInfo` struct represents the information related to enemies in the game.
+/// The `EnemyInfo` struct represents the information related to enemies in the game,
+/// inferred from the vision and the referee.
 #[derive(Serialize, Clone, Default, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct EnemyInfo;
+pub struct EnemyInfo {
+    /// Whether the robot is the goalkeeper of the enemy team, according to the referee.
+    pub is_goalkeeper: bool,
+    /// Whether the robot is likely controlling the ball, i.e. the ball is close to and in
+    /// front of its dribbler.
+    pub controls_ball: bool,
+    /// The unit vector of the direction in which the robot would kick, which is the direction
+    /// it is facing.
+    pub kick_direction: Vector2<f64>,
+    /// How dangerous the robot is for our goal, between 0 (harmless) and 1 (about to score).
+    pub threat: f64,
+}
 
 /// The `RobotVelocity` struct represents the velocity of a robot in the SSL.
 #[derive(Serialize, Default, Debug, Clone)]
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// The `TeamColor` enum represents the color of a team in the SSL game, either blue or yellow.
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TeamColor {
     Blue,
@@ -18,19 +18,47 @@ impl TeamColor {
     }
 }
 
-/// The `Team` struct represents a team in the SSL game, including its color and optional name.
+/// The `Team` struct represents a team in the SSL game, including its color, optional name
+/// and the information given by the game controller.
 #[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Team {
     /// The color of the team.
-    color: TeamColor,
+    pub color: TeamColor,
     /// The name of the team, if provided.
-    name: Option<String>,
+    pub name: Option<String>,
+    /// The number of goals scored by the team.
+    pub score: u32,
+    /// The number of red cards issued to the team since the beginning of the game.
+    pub red_cards: u32,
+    /// The total number of yellow cards issued to the team since the beginning of the game.
+    pub yellow_cards: u32,
+    /// The time left in seconds on each active yellow card, from the smallest to the largest.
+    pub yellow_card_times: Vec<f64>,
+    /// The number of timeouts the team can still call.
+    pub timeouts: u32,
+    /// The timeout time the team can still use, in seconds.
+    pub timeout_time: f64,
+    /// The id of the goalkeeper of the team, known once the game controller sent it.
+    pub goalkeeper: Option<u8>,
+    /// The maximum number of robots allowed on the field for the team, if known.
+    pub max_allowed_bots: Option<u32>,
 }
 
 impl Team {
     /// Creates a new team with the given color.
     pub fn with_color(color: TeamColor) -> Self {
-        Self { color, name: None }
+        Self {
+            color,
+            name: None,
+            score: 0,
+            red_cards: 0,
+            yellow_cards: 0,
+            yellow_card_times: vec![],
+            timeouts: 0,
+            timeout_time: 0.0,
+            goalkeeper: None,
+            max_allowed_bots: None,
+        }
     }
 }
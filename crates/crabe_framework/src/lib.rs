@@ -2,12 +2,18 @@
 //!
 //! This crate provides shared utilities and settings for the CRAbE project.
 //!
+//! - The `clock` module contains the source of time shared by the components.
 //! - The `config` module contains settings that are common to multiple CRAbE
-//! crates.
+//!   crates.
 //! - The `constant` module contains constants that are common to multiple
-//! CRAbE crates.
+//!   CRAbE crates.
 //! - The `component` module contains traits and structs that defines the component architecture
-//! used in the CRAbE project
+//!   used in the CRAbE project
+//! - The `parameter` module contains the runtime parameters that the tools can change while the
+//!   system runs.
+//! - The `robot_profile` module contains the description of the hardware of our robots.
+//! - The `registry` module contains the registry used to choose the implementations of the
+//!   components by name.
 //! - The `data` module contains definitions of structs and enums used to represent and manipulate
 //!   data in the robocup SSL system.
 //!
@@ -15,6 +21,10 @@
 //! should not be used on its own. Please refer to the individual modules for
 //! more information and usage instructions.
 
+/// This module contains the `Clock` trait that the components use to read the time,
+/// with a wall clock for normal runs and a manual clock for deterministic runs.
+pub mod clock;
+
 /// This module contains the structure of the configuration settings that are
 /// shared across multiple other crates in the CRAbE project. These settings are
 /// meant to be accessed and used by other CRAbE crates as a way of maintaining
@@ -51,6 +61,19 @@ pub mod component;
 /// for working with these data types.
 pub mod data;
 
+/// This module contains the `Parameters` registry, in which the components declare their gains,
+/// limits and timeouts so that the tools can tune them live.
+pub mod parameter;
+
+/// This module contains the `RobotProfile` struct, which describes the dimensions and the limits
+/// of our robots for the simulator and the guards.
+pub mod robot_profile;
+
+/// This module contains the `Registry` struct that maps names to the factories of the
+/// sub-components (filters, guards, managers, strategies, ...), so that they can be selected
+/// from the command line.
+pub mod registry;
+
 /// This module contains constants that are used throughout the CRAbE project.
 /// These constants are meant to be accessed and used by other CRAbE crates as a
 /// way of maintaining consistency across the project.
@@ -0,0 +1,264 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// The `ParameterValue` trait is implemented by the types of the runtime parameters, which are
+/// stored as `f64`.
+pub trait ParameterValue: Copy {
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl ParameterValue for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl ParameterValue for f32 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+/// A duration is stored in seconds.
+impl ParameterValue for Duration {
+    fn to_f64(self) -> f64 {
+        self.as_secs_f64()
+    }
+
+    fn from_f64(value: f64) -> Self {
+        Duration::from_secs_f64(value)
+    }
+}
+
+/// The `Parameter` struct is a handle on the value of a runtime parameter, which the tools can
+/// change at any time. Reading it is cheap enough to be done at every step.
+#[derive(Clone, Debug)]
+pub struct Parameter<T> {
+    value: Arc<AtomicU64>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T: ParameterValue> Parameter<T> {
+    /// Returns the current value of the parameter.
+    pub fn get(&self) -> T {
+        T::from_f64(f64::from_bits(self.value.load(Ordering::Relaxed)))
+    }
+}
+
+/// The `ParameterInfo` struct describes a runtime parameter for the tools.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterInfo {
+    pub name: String,
+    pub description: String,
+    pub value: f64,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+struct Entry {
+    info: ParameterInfo,
+    value: Arc<AtomicU64>,
+}
+
+impl Entry {
+    fn info(&self) -> ParameterInfo {
+        ParameterInfo {
+            value: f64::from_bits(self.value.load(Ordering::Relaxed)),
+            ..self.info.clone()
+        }
+    }
+
+    fn set(&self, value: f64) -> Result<ParameterInfo, String> {
+        if !(self.info.min..=self.info.max).contains(&value) {
+            return Err(format!(
+                "{} is out of the bounds of `{}` [{}, {}]",
+                value, self.info.name, self.info.min, self.info.max
+            ));
+        }
+        self.value.store(value.to_bits(), Ordering::Relaxed);
+        Ok(self.info())
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: BTreeMap<String, Entry>,
+    /// The values loaded for parameters that are not declared yet.
+    pending: HashMap<String, f64>,
+}
+
+/// The `Parameters` struct is a registry of the runtime parameters: the gains, limits and
+/// timeouts that the components declare with a default value and bounds, and that the tools
+/// can list and change while the system runs.
+#[derive(Clone, Default)]
+pub struct Parameters {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Parameters {
+    /// Declares the parameter `name` and returns a handle on its value. Declaring a parameter
+    /// again returns a handle on the same value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: The name of the parameter, prefixed by its component (e.g. `move_to.speed`).
+    /// * `description`: What the parameter does, with its unit.
+    /// * `default`: The initial value of the parameter.
+    /// * `min`, `max`: The bounds of the values accepted from the tools.
+    pub fn declare<T: ParameterValue>(
+        &self,
+        name: &str,
+        description: &str,
+        default: T,
+        min: T,
+        max: T,
+    ) -> Parameter<T> {
+        let mut inner = self.inner.lock().expect("parameters poisoned");
+        let value = match inner.entries.get(name) {
+            Some(entry) => entry.value.clone(),
+            None => {
+                let entry = Entry {
+                    info: ParameterInfo {
+                        name: name.to_string(),
+                        description: description.to_string(),
+                        value: default.to_f64(),
+                        default: default.to_f64(),
+                        min: min.to_f64(),
+                        max: max.to_f64(),
+                    },
+                    value: Arc::new(AtomicU64::new(default.to_f64().to_bits())),
+                };
+                if let Some(loaded) = inner.pending.remove(name) {
+                    if let Err(e) = entry.set(loaded) {
+                        warn!("Ignoring the saved value of a parameter: {}", e);
+                    }
+                }
+                let value = entry.value.clone();
+                inner.entries.insert(name.to_string(), entry);
+                value
+            }
+        };
+        Parameter {
+            value,
+            _type: PhantomData,
+        }
+    }
+
+    /// Returns the declared parameters, sorted by name.
+    pub fn list(&self) -> Vec<ParameterInfo> {
+        let inner = self.inner.lock().expect("parameters poisoned");
+        inner.entries.values().map(Entry::info).collect()
+    }
+
+    /// Returns the parameter `name`, if it is declared.
+    pub fn get(&self, name: &str) -> Option<ParameterInfo> {
+        let inner = self.inner.lock().expect("parameters poisoned");
+        inner.entries.get(name).map(Entry::info)
+    }
+
+    /// Changes the value of the parameter `name`, within its bounds.
+    pub fn set(&self, name: &str, value: f64) -> Result<ParameterInfo, String> {
+        let inner = self.inner.lock().expect("parameters poisoned");
+        inner
+            .entries
+            .get(name)
+            .ok_or_else(|| format!("unknown parameter `{}`", name))?
+            .set(value)
+    }
+
+    /// Saves the values of the parameters in the JSON file `path`.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let values: BTreeMap<_, _> = self
+            .list()
+            .into_iter()
+            .map(|info| (info.name, info.value))
+            .collect();
+        let json = serde_json::to_string_pretty(&values).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        info!("Parameters saved to {}", path.display());
+        Ok(())
+    }
+
+    /// Loads the values of the parameters saved in the JSON file `path`. The values of the
+    /// parameters that are not declared yet are applied when they are declared.
+    pub fn load(&self, path: &Path) -> Result<(), String> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let values: HashMap<String, f64> = serde_json::from_str(&json)
+            .map_err(|e| format!("invalid {}: {}", path.display(), e))?;
+
+        let mut inner = self.inner.lock().expect("parameters poisoned");
+        for (name, value) in values {
+            match inner.entries.get(&name) {
+                Some(entry) => {
+                    if let Err(e) = entry.set(value) {
+                        warn!("Ignoring the saved value of a parameter: {}", e);
+                    }
+                }
+                None => {
+                    inner.pending.insert(name, value);
+                }
+            }
+        }
+        info!("Parameters loaded from {}", path.display());
+        Ok(())
+    }
+}
+
+/// Returns the runtime parameters of the system, shared by all the components.
+pub fn parameters() -> &'static Parameters {
+    static PARAMETERS: OnceLock<Parameters> = OnceLock::new();
+    PARAMETERS.get_or_init(Parameters::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters_are_changed_within_their_bounds() {
+        let parameters = Parameters::default();
+        let speed = parameters.declare("move_to.speed", "Speed gain", 1.5, 0.0, 5.0);
+        let timeout = parameters.declare(
+            "inactive.timeout",
+            "Timeout (s)",
+            Duration::from_secs(2),
+            Duration::ZERO,
+            Duration::from_secs(10),
+        );
+        assert_eq!(speed.get(), 1.5);
+
+        parameters.set("move_to.speed", 2.5).unwrap();
+        assert_eq!(speed.get(), 2.5);
+        assert!(parameters.set("move_to.speed", 6.0).is_err());
+        assert!(parameters.set("unknown", 1.0).is_err());
+        assert_eq!(speed.get(), 2.5);
+
+        // The handles of the same parameter share its value.
+        let again = parameters.declare("move_to.speed", "Speed gain", 1.5, 0.0, 5.0);
+        assert_eq!(again.get(), 2.5);
+
+        parameters.set("inactive.timeout", 0.5).unwrap();
+        assert_eq!(timeout.get(), Duration::from_millis(500));
+        let names: Vec<_> = parameters.list().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["inactive.timeout", "move_to.speed"]);
+    }
+}
@@ -0,0 +1,108 @@
+/// A factory building an implementation of `T` from an argument of type `A`.
+pub type Factory<T, A> = fn(A) -> Box<T>;
+
+/// The `Registry` struct maps names to the factories of the implementations of a trait `T`
+/// (filters, guards, managers, ...), so that the implementations to use can be chosen by name
+/// from the command line instead of being hardcoded in the pipelines.
+///
+/// The factories build a boxed implementation from an argument of type `A`, which holds what
+/// the implementations may need (a clock, a robot id, ...).
+pub struct Registry<T: ?Sized, A = ()> {
+    entries: Vec<(&'static str, Factory<T, A>)>,
+}
+
+impl<T: ?Sized, A> Registry<T, A> {
+    /// Creates an empty `Registry`.
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Registers the factory `factory` under the name `name`, replacing any factory registered
+    /// under the same name.
+    pub fn register(mut self, name: &'static str, factory: Factory<T, A>) -> Self {
+        self.entries.retain(|(n, _)| *n != name);
+        self.entries.push((name, factory));
+        self
+    }
+
+    /// Returns the registered names, in the order of registration.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.entries.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Builds the implementation registered under `name`, or returns `None` if there is none.
+    pub fn create(&self, name: &str, args: A) -> Option<Box<T>> {
+        self.entries
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, factory)| factory(args))
+    }
+}
+
+impl<T: ?Sized, A: Clone> Registry<T, A> {
+    /// Builds the implementations registered under `names`, in order, with the same argument.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a name is not registered, the names are expected to be checked when the
+    /// configuration is parsed.
+    pub fn create_all(&self, names: &[String], args: A) -> Vec<Box<T>> {
+        names
+            .iter()
+            .map(|name| {
+                self.create(name, args.clone())
+                    .unwrap_or_else(|| panic!("no component registered under `{}`", name))
+            })
+            .collect()
+    }
+}
+
+impl<T: ?Sized, A> Default for Registry<T, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    trait Named {
+        fn name(&self) -> String;
+    }
+
+    struct Fixed(&'static str);
+
+    impl Named for Fixed {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    struct WithId(u8);
+
+    impl Named for WithId {
+        fn name(&self) -> String {
+            format!("robot {}", self.0)
+        }
+    }
+
+    #[test]
+    fn components_are_created_by_name() {
+        let registry = Registry::<dyn Named, u8>::new()
+            .register("fixed", |_| Box::new(Fixed("fixed")))
+            .register("robot", |id| Box::new(WithId(id)));
+
+        assert_eq!(registry.names(), vec!["fixed", "robot"]);
+        assert_eq!(registry.create("robot", 3).unwrap().name(), "robot 3");
+        assert!(registry.create("unknown", 0).is_none());
+
+        let names = vec!["robot".to_string(), "fixed".to_string()];
+        let created: Vec<_> = registry
+            .create_all(&names, 1)
+            .iter()
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(created, vec!["robot 1", "fixed"]);
+    }
+}
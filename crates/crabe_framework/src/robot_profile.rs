@@ -0,0 +1,144 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// The `RobotProfile` struct describes the hardware of our robots: their dimensions, the layout
+/// of their wheels and the limits of their motion and kicks. It is sent to the simulator, so that
+/// it simulates our robots rather than its default ones, and bounds the orders of the actions and
+/// the commands of the guards.
+///
+/// The profile is read from a TOML file with the same fields, the missing ones keeping the
+/// values of our robots.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RobotProfile {
+    /// Radius of the robot in meters.
+    pub radius: f64,
+    /// Height of the robot in meters.
+    pub height: f64,
+    /// Mass of the robot in kilograms.
+    pub mass: f64,
+    /// Distance from the center of the robot to its dribbler in meters.
+    pub center_to_dribbler: f64,
+    pub wheel_angles: WheelAngles,
+    pub limits: MotionLimits,
+    pub kick: KickLimits,
+}
+
+/// The angles of the wheels of the robot in degrees, clockwise from its front.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WheelAngles {
+    pub front_right: f64,
+    pub back_right: f64,
+    pub back_left: f64,
+    pub front_left: f64,
+}
+
+/// The limits of the motion of the robot.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MotionLimits {
+    /// Maximum linear velocity in m.s-1.
+    pub max_linear_velocity: f64,
+    /// Maximum angular velocity in rad.s-1.
+    pub max_angular_velocity: f64,
+    /// Maximum linear acceleration when speeding up in m.s-2.
+    pub max_linear_acceleration: f64,
+    /// Maximum angular acceleration when speeding up in rad.s-2.
+    pub max_angular_acceleration: f64,
+    /// Maximum linear deceleration when braking in m.s-2.
+    pub max_linear_deceleration: f64,
+    /// Maximum angular deceleration when braking in rad.s-2.
+    pub max_angular_deceleration: f64,
+}
+
+/// The limits of the kicks of the robot.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KickLimits {
+    /// Maximum speed of the ball after a straight kick in m.s-1.
+    pub max_straight_speed: f64,
+    /// Maximum speed of the ball after a chip kick in m.s-1.
+    pub max_chip_speed: f64,
+}
+
+impl Default for RobotProfile {
+    fn default() -> Self {
+        Self {
+            radius: 0.09,
+            height: 0.15,
+            mass: 2.3,
+            center_to_dribbler: 0.075,
+            wheel_angles: Default::default(),
+            limits: Default::default(),
+            kick: Default::default(),
+        }
+    }
+}
+
+impl Default for WheelAngles {
+    fn default() -> Self {
+        Self {
+            front_right: 60.0,
+            back_right: 135.0,
+            back_left: 225.0,
+            front_left: 300.0,
+        }
+    }
+}
+
+impl Default for MotionLimits {
+    fn default() -> Self {
+        Self {
+            max_linear_velocity: 2.0,
+            max_angular_velocity: std::f64::consts::PI,
+            max_linear_acceleration: 3.0,
+            max_angular_acceleration: 20.0,
+            max_linear_deceleration: 4.0,
+            max_angular_deceleration: 20.0,
+        }
+    }
+}
+
+impl Default for KickLimits {
+    fn default() -> Self {
+        Self {
+            max_straight_speed: 6.5,
+            max_chip_speed: 5.0,
+        }
+    }
+}
+
+impl RobotProfile {
+    /// Reads the robot profile from the TOML file `path`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let path = Path::new(path);
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("invalid {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_keep_their_default() {
+        let profile: RobotProfile = toml::from_str(
+            r#"
+            mass = 2.5
+            [limits]
+            max_linear_velocity = 3.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(profile.mass, 2.5);
+        assert_eq!(profile.limits.max_linear_velocity, 3.0);
+        assert_eq!(profile.radius, RobotProfile::default().radius);
+        assert_eq!(profile.wheel_angles, WheelAngles::default());
+
+        assert!(toml::from_str::<RobotProfile>("unknown = 1.0").is_err());
+    }
+}
@@ -1,2 +0,0 @@
-pub const MAX_LINEAR: f32 = 2.;
-pub const MAX_ANGULAR: f32 = std::f32::consts::PI;
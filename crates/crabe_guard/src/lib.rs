@@ -1,3 +1,2 @@
-pub mod constant;
 pub mod pipeline;
 pub mod speed;
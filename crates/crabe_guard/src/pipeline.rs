@@ -1,13 +1,28 @@
 use crate::speed::SpeedGuard;
+use clap::builder::PossibleValuesParser;
 use clap::Args;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::component::{Component, GuardComponent};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::data::output::CommandMap;
-use crabe_framework::data::tool::ToolCommands;
+use crabe_framework::data::plot::Plotter;
+use crabe_framework::data::tool::{ToolCommand, ToolCommands};
 use crabe_framework::data::world::World;
+use crabe_framework::registry::Registry;
+use crabe_framework::robot_profile::RobotProfile;
+use log::{info, warn};
 
 #[derive(Args)]
-pub struct GuardConfig {}
+pub struct GuardConfig {
+    /// Guards applied to the commands before they are sent, in order.
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "speed",
+        value_parser = PossibleValuesParser::new(registry().names())
+    )]
+    pub guards: Vec<String>,
+}
 
 pub trait Guard {
     fn guard(
@@ -18,14 +33,108 @@ pub trait Guard {
     );
 }
 
+/// The `GuardContext` struct holds what the guards are built with.
+#[derive(Clone)]
+pub struct GuardContext {
+    /// The plotter of the system.
+    pub plotter: Plotter,
+    /// The profile of our robots, whose limits bound the commands.
+    pub robot_profile: RobotProfile,
+}
+
+/// Returns the registry of the guards.
+pub fn registry() -> Registry<dyn Guard, GuardContext> {
+    Registry::<dyn Guard, GuardContext>::new().register("speed", |context| {
+        Box::new(SpeedGuard::new(&context.robot_profile.limits))
+    })
+}
+
+/// The `GuardEntry` struct is a guard of the pipeline with its name, so that the tools can
+/// enable or disable it.
+struct GuardEntry {
+    name: String,
+    guard: Box<dyn Guard>,
+    enabled: bool,
+}
+
 pub struct GuardPipeline {
-    guards: Vec<Box<dyn Guard>>,
+    guards: Vec<GuardEntry>,
+    /// Whether all the robots are stopped after a `HaltAll` from the tools.
+    halted: bool,
+    /// The context given to the guards created later on.
+    context: GuardContext,
 }
 
 impl GuardPipeline {
-    pub fn with_config(_guard_cfg: GuardConfig, _common_cfg: &CommonConfig) -> Self {
+    pub fn with_config(
+        guard_cfg: GuardConfig,
+        common_cfg: &CommonConfig,
+        _clock: &SharedClock,
+        plotter: &Plotter,
+    ) -> Self {
+        let context = GuardContext {
+            plotter: plotter.clone(),
+            robot_profile: common_cfg.robot_profile(),
+        };
+        let guards = registry().create_all(&guard_cfg.guards, context.clone());
         Self {
-            guards: vec![Box::<SpeedGuard>::default()],
+            guards: guard_cfg
+                .guards
+                .into_iter()
+                .zip(guards)
+                .map(|(name, guard)| GuardEntry {
+                    name,
+                    guard,
+                    enabled: true,
+                })
+                .collect(),
+            halted: false,
+            context,
+        }
+    }
+
+    /// Enables the guard `name`, adding it at the end of the guards if it isn't in the pipeline.
+    fn enable(&mut self, name: &str) {
+        if let Some(entry) = self.guards.iter_mut().find(|e| e.name == name) {
+            entry.enabled = true;
+        } else if let Some(guard) = registry().create(name, self.context.clone()) {
+            self.guards.push(GuardEntry {
+                name: name.to_string(),
+                guard,
+                enabled: true,
+            });
+        } else {
+            warn!("Unknown guard requested by a tool: {}", name);
+            return;
+        }
+        info!("Guard {} enabled", name);
+    }
+
+    fn disable(&mut self, name: &str) {
+        match self.guards.iter_mut().find(|e| e.name == name) {
+            Some(entry) => {
+                entry.enabled = false;
+                info!("Guard {} disabled", name);
+            }
+            None => warn!("Cannot disable the guard {}, it isn't running", name),
+        }
+    }
+
+    fn apply_tool_commands(&mut self, tools_commands: &ToolCommands) {
+        for command in tools_commands {
+            match command {
+                ToolCommand::HaltAll => {
+                    warn!("All robots halted by a tool");
+                    self.halted = true;
+                }
+                ToolCommand::Resume if self.halted => {
+                    info!("Robots released by a tool");
+                    self.halted = false;
+                }
+                ToolCommand::EnableGuard(name) => self.enable(name),
+                ToolCommand::DisableGuard(name) => self.disable(name),
+                _ => {}
+            }
         }
     }
 }
@@ -41,8 +150,19 @@ impl GuardComponent for GuardPipeline {
         commands: &mut CommandMap,
         tools_commands: &mut ToolCommands,
     ) {
+        self.apply_tool_commands(tools_commands);
+        if self.halted {
+            // Every robot we know of gets a stop command, even if nothing commanded it.
+            world.allies_bot.keys().for_each(|id| {
+                commands.entry(*id).or_default();
+            });
+            commands.values_mut().for_each(|c| *c = Default::default());
+            return;
+        }
+
         self.guards
             .iter_mut()
-            .for_each(|x| x.guard(world, commands, tools_commands));
+            .filter(|e| e.enabled)
+            .for_each(|e| e.guard.guard(world, commands, tools_commands));
     }
 }
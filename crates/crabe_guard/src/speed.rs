@@ -1,29 +1,37 @@
-use crate::constant::{MAX_ANGULAR, MAX_LINEAR};
 use crate::pipeline::Guard;
 use crabe_framework::data::output::CommandMap;
 use crabe_framework::data::tool::ToolCommands;
 use crabe_framework::data::world::World;
+use crabe_framework::parameter::{parameters, Parameter};
+use crabe_framework::robot_profile::MotionLimits;
 use log::warn;
 
 pub struct SpeedGuard {
-    max_linear: f32,
-    max_angular: f32,
+    max_linear: Parameter<f32>,
+    max_angular: Parameter<f32>,
 }
 
 impl SpeedGuard {
-    pub fn new(max_linear: f32, max_angular: f32) -> Self {
+    /// Creates a new `SpeedGuard`, whose limits are the runtime parameters `guard.speed.*`.
+    /// They are the velocity limits of the robot profile by default, and can only be lowered.
+    pub fn new(limits: &MotionLimits) -> Self {
+        let max_linear = limits.max_linear_velocity as f32;
+        let max_angular = limits.max_angular_velocity as f32;
         Self {
-            max_linear,
-            max_angular,
-        }
-    }
-}
-
-impl Default for SpeedGuard {
-    fn default() -> Self {
-        Self {
-            max_linear: MAX_LINEAR,
-            max_angular: MAX_ANGULAR,
+            max_linear: parameters().declare(
+                "guard.speed.max_linear",
+                "Maximum linear velocity sent to the robots, in m.s-1",
+                max_linear,
+                0.0,
+                max_linear,
+            ),
+            max_angular: parameters().declare(
+                "guard.speed.max_angular",
+                "Maximum angular velocity sent to the robots, in rad.s-1",
+                max_angular,
+                0.0,
+                max_angular,
+            ),
         }
     }
 }
@@ -35,6 +43,8 @@ impl Guard for SpeedGuard {
         commands: &mut CommandMap,
         _tool_commands: &mut ToolCommands,
     ) {
+        let max_linear = self.max_linear.get();
+        let max_angular = self.max_angular.get();
         commands.iter_mut().for_each(|(_id, command)| {
             // Replacing any NaN values that might be computed to 0.
             // nalgebra docs mention you shouldn't compare with f32::NaN and should use the .is_nan() method instead
@@ -44,7 +54,7 @@ impl Guard for SpeedGuard {
             } else {
                 command.forward_velocity = command
                     .forward_velocity
-                    .clamp(-self.max_linear, self.max_linear);
+                    .clamp(-max_linear, max_linear);
             }
 
             if command.left_velocity.is_nan() {
@@ -53,16 +63,65 @@ impl Guard for SpeedGuard {
             } else {
                 command.left_velocity = command
                     .left_velocity
-                    .clamp(-self.max_linear, self.max_linear);
+                    .clamp(-max_linear, max_linear);
             }
 
             if command.angular_velocity.is_nan() {
                 warn!("An attempt was made to send NaN instead of a valid value in angular_velocity. It has been adjusted to 0.");
+                command.angular_velocity = 0.;
+            } else {
                 command.angular_velocity = command
                     .angular_velocity
-                    .clamp(-self.max_angular, self.max_angular);
+                    .clamp(-max_angular, max_angular);
             }
 
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::config::CommonConfig;
+    use crabe_framework::data::output::Command;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn velocities_are_clamped_and_nan_values_zeroed() {
+        let mut guard = SpeedGuard::new(&MotionLimits::default());
+        let world = World::with_config(&CommonConfig {
+            yellow: false,
+            real: false,
+            synchronous: false,
+            robot_profile: None,
+        });
+        let mut commands = CommandMap::new();
+        commands.insert(
+            0,
+            Command {
+                forward_velocity: 10.0,
+                left_velocity: f32::NAN,
+                angular_velocity: -10.0,
+                ..Default::default()
+            },
+        );
+        commands.insert(
+            1,
+            Command {
+                forward_velocity: f32::NAN,
+                left_velocity: -1.0,
+                angular_velocity: f32::NAN,
+                ..Default::default()
+            },
+        );
+
+        guard.guard(&world, &mut commands, &mut ToolCommands::default());
+
+        assert_eq!(commands[&0].forward_velocity, 2.0);
+        assert_eq!(commands[&0].left_velocity, 0.0);
+        assert_eq!(commands[&0].angular_velocity, -PI);
+        assert_eq!(commands[&1].forward_velocity, 0.0);
+        assert_eq!(commands[&1].left_velocity, -1.0);
+        assert_eq!(commands[&1].angular_velocity, 0.0);
+    }
+}
@@ -0,0 +1,260 @@
+//! A fake base station on a pseudo-terminal, to test the real output path without the radio.
+//! It prints the path of the pseudo-terminal to give to CRAbE with `--real --usb-port`.
+
+use clap::Parser;
+use crabe_framework::robot_profile::RobotProfile;
+use crabe_io::communication::frame::{negotiate_version, Frame, FrameDecoder, FrameKind};
+use crabe_io::constant::{BASE_MAX_COMMANDS, BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION};
+use crabe_protocol::protobuf::robot_packet::{BaseCommand, BaseFeedback, BaseToPc, PcToBase};
+use prost::Message;
+use serialport::{SerialPort, TTYPort};
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+use std::io::{Read, Write};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// Distance from the center of the robot to its wheels in meters.
+const WHEEL_DISTANCE: f32 = 0.08;
+/// Radius of the wheels in meters.
+const WHEEL_RADIUS: f32 = 0.027;
+/// The robots stop when they don't receive commands for this duration, like the real ones.
+const COMMAND_TIMEOUT: Duration = Duration::from_millis(500);
+/// The feedback is sent as long as CRAbE sent a frame during this duration.
+const LINK_TIMEOUT: Duration = Duration::from_secs(1);
+const BATTERY_VOLTAGE: f32 = 16.0;
+
+#[derive(Parser)]
+#[command(about = "Fake base station on a pseudo-terminal")]
+struct Args {
+    /// Rate of the feedback sent to CRAbE in Hz.
+    #[arg(long, default_value_t = 100.0)]
+    rate: f64,
+    /// TOML file describing the hardware of the robots (see `robots/namec.toml`), giving their
+    /// wheels and accelerations. Our robots are used by default.
+    #[arg(long, value_name = "FILE", value_parser = RobotProfile::load)]
+    robot_profile: Option<RobotProfile>,
+}
+
+/// The motion of the robots, taken from their profile.
+struct Model {
+    /// Angles of the wheels of the motors 1 to 4 in radians, clockwise from the front.
+    wheel_angles: [f32; 4],
+    /// Accelerations when speeding up, forward, to the left and angular.
+    accelerations: [f32; 3],
+    /// Decelerations when braking, forward, to the left and angular.
+    decelerations: [f32; 3],
+}
+
+impl Model {
+    fn new(profile: &RobotProfile) -> Self {
+        let angles = &profile.wheel_angles;
+        let limits = &profile.limits;
+        let linear = limits.max_linear_acceleration as f32;
+        let angular = limits.max_angular_acceleration as f32;
+        let linear_brake = limits.max_linear_deceleration as f32;
+        let angular_brake = limits.max_angular_deceleration as f32;
+        Self {
+            wheel_angles: [
+                angles.front_right,
+                angles.back_right,
+                angles.back_left,
+                angles.front_left,
+            ]
+            .map(|angle| angle.to_radians() as f32),
+            accelerations: [linear, linear, angular],
+            decelerations: [linear_brake, linear_brake, angular_brake],
+        }
+    }
+}
+
+/// The kinematic state of a robot, in the frame where it started.
+struct Robot {
+    command: BaseCommand,
+    last_command: Instant,
+    /// Velocity forward, to the left and angular.
+    velocity: [f32; 3],
+    position: [f32; 2],
+    orientation: f32,
+}
+
+impl Robot {
+    fn new(command: BaseCommand, now: Instant) -> Self {
+        Self {
+            command,
+            last_command: now,
+            velocity: [0.0; 3],
+            position: [0.0; 2],
+            orientation: 0.0,
+        }
+    }
+
+    fn step(&mut self, model: &Model, dt: f32, now: Instant) {
+        let target = if now - self.last_command < COMMAND_TIMEOUT {
+            [
+                self.command.normal_velocity,
+                self.command.tangential_velocity,
+                self.command.angular_velocity,
+            ]
+        } else {
+            [0.0; 3]
+        };
+        for (axis, (velocity, target)) in self.velocity.iter_mut().zip(target).enumerate() {
+            let braking = target * *velocity < 0.0 || target.abs() < velocity.abs();
+            let max_delta = if braking {
+                model.decelerations[axis]
+            } else {
+                model.accelerations[axis]
+            } * dt;
+            *velocity += (target - *velocity).clamp(-max_delta, max_delta);
+        }
+
+        let [forward, left, angular] = self.velocity;
+        let (sin, cos) = self.orientation.sin_cos();
+        self.position[0] += (forward * cos - left * sin) * dt;
+        self.position[1] += (forward * sin + left * cos) * dt;
+        self.orientation = (self.orientation + angular * dt + PI).rem_euclid(2.0 * PI) - PI;
+    }
+
+    fn feedback(&self, model: &Model, id: u32) -> BaseFeedback {
+        let [forward, left, angular] = self.velocity;
+        // The angles are clockwise, a wheel at the angle `angle` rolls towards
+        // (sin(angle), cos(angle)) when it turns the robot counter-clockwise.
+        let [motor_1_speed, motor_2_speed, motor_3_speed, motor_4_speed] =
+            model.wheel_angles.map(|angle| {
+                (angle.sin() * forward + angle.cos() * left + WHEEL_DISTANCE * angular)
+                    / WHEEL_RADIUS
+            });
+        BaseFeedback {
+            robot_id: id,
+            motor_1_speed,
+            motor_2_speed,
+            motor_3_speed,
+            motor_4_speed,
+            voltage: BATTERY_VOLTAGE,
+            // There is no ball: the robots pretend to hold it while dribbling.
+            ir: self.command.dribbler > 0.0,
+        }
+    }
+}
+
+fn write_frame(port: &mut TTYPort, frame: Frame) {
+    if let Err(e) = port.write_all(&frame.encode()) {
+        eprintln!("Cannot write to the pseudo-terminal: {}", e);
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    // The slave end must stay open, otherwise the reads on the master end fail until CRAbE opens
+    // it.
+    let (mut master, slave) = TTYPort::pair().expect("Failed to open a pseudo-terminal");
+    println!(
+        "Fake base station on {}",
+        slave.name().expect("The pseudo-terminal has no name")
+    );
+
+    let period = Duration::from_secs_f64(1.0 / args.rate);
+    let model = Model::new(&args.robot_profile.unwrap_or_default());
+    let mut decoder = FrameDecoder::default();
+    let mut version = BASE_PROTOCOL_VERSION;
+    let mut robots: BTreeMap<u32, Robot> = BTreeMap::new();
+    let mut last_step = Instant::now();
+    let mut last_report = Instant::now();
+    let mut last_frame: Option<Instant> = None;
+
+    loop {
+        let available = master.bytes_to_read().unwrap_or(0) as usize;
+        if available > 0 {
+            let mut bytes = vec![0; available];
+            match master.read(&mut bytes) {
+                Ok(read) => decoder.push(&bytes[..read]),
+                Err(e) => eprintln!("Cannot read the pseudo-terminal: {}", e),
+            }
+        }
+
+        let now = Instant::now();
+        while let Some(frame) = decoder.next_frame() {
+            last_frame = Some(now);
+            match frame.kind {
+                FrameKind::Hello => {
+                    if let [min, max] = frame.payload[..] {
+                        match negotiate_version(
+                            (BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION),
+                            (min, max),
+                        ) {
+                            Some(v) => version = v,
+                            None => eprintln!("No common protocol version with {}..={}", min, max),
+                        }
+                    }
+                    println!("Hello from CRAbE, using the protocol version {}", version);
+                    write_frame(
+                        &mut master,
+                        Frame {
+                            version: BASE_PROTOCOL_VERSION,
+                            kind: FrameKind::Hello,
+                            payload: vec![BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION],
+                        },
+                    );
+                }
+                FrameKind::Data if frame.version != version => {
+                    eprintln!("Ignoring a frame of protocol version {}", frame.version);
+                }
+                FrameKind::Data => match PcToBase::decode(frame.payload.as_slice()) {
+                    Ok(packet) => {
+                        for command in packet.commands {
+                            robots
+                                .entry(command.robot_id)
+                                .and_modify(|robot| {
+                                    robot.command = command.clone();
+                                    robot.last_command = now;
+                                })
+                                .or_insert_with(|| Robot::new(command, now));
+                        }
+                    }
+                    Err(e) => eprintln!("Cannot decode the commands: {}", e),
+                },
+            }
+        }
+
+        let dt = (now - last_step).as_secs_f32();
+        last_step = now;
+        robots
+            .values_mut()
+            .for_each(|robot| robot.step(&model, dt, now));
+
+        // Nobody reads the feedback when CRAbE is not running, it would fill the pseudo-terminal.
+        let connected = last_frame.is_some_and(|last_frame| now - last_frame < LINK_TIMEOUT);
+        if connected {
+            let feedbacks: Vec<_> = robots
+                .iter()
+                .map(|(id, robot)| robot.feedback(&model, *id))
+                .collect();
+            for chunk in feedbacks.chunks(BASE_MAX_COMMANDS) {
+                let packet = BaseToPc {
+                    feedbacks: chunk.to_vec(),
+                };
+                write_frame(
+                    &mut master,
+                    Frame {
+                        version,
+                        kind: FrameKind::Data,
+                        payload: packet.encode_to_vec(),
+                    },
+                );
+            }
+        }
+
+        if now - last_report > Duration::from_secs(1) {
+            last_report = now;
+            for (id, robot) in &robots {
+                println!(
+                    "robot #{}: position ({:.2}, {:.2}), orientation {:.2}, velocity {:.2?}",
+                    id, robot.position[0], robot.position[1], robot.orientation, robot.velocity
+                );
+            }
+        }
+
+        sleep(period);
+    }
+}
@@ -0,0 +1,45 @@
+//! Records the vision, the game controller and the tracker to an SSL log file, without running
+//! the rest of the system.
+
+use clap::Parser;
+use crabe_framework::clock::{SharedClock, WallClock};
+use crabe_framework::component::InputComponent;
+use crabe_framework::config::CommonConfig;
+use crabe_io::league::simulator::synchronous::vision_channel;
+use crabe_io::pipeline::input::{InputConfig, InputPipeline};
+use crabe_io::recorder::Recorder;
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(about = "Records the league traffic to an SSL log file")]
+struct Args {
+    #[command(flatten)]
+    common: CommonConfig,
+
+    #[command(flatten)]
+    input: InputConfig,
+}
+
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+    let Some(path) = &args.input.record else {
+        eprintln!("The log file must be given with --record");
+        std::process::exit(1);
+    };
+
+    let clock: SharedClock = Arc::new(WallClock);
+    let recorder =
+        Recorder::with_config(Some(path), &clock).expect("Failed to create the log file");
+    // Nothing steps the synchronous simulator, its vision is never received.
+    let (_, sync_vision) = vision_channel();
+    let mut input =
+        InputPipeline::with_config(args.input, &args.common, &clock, &sync_vision, &recorder);
+    // The receivers record the packets as they arrive, each step flushes them.
+    loop {
+        input.step(&mut Default::default());
+        sleep(Duration::from_millis(1));
+    }
+}
@@ -5,7 +5,9 @@ mod udp_transceiver;
 pub use self::udp_transceiver::UDPTransceiver;
 
 mod websocket_transceiver;
-pub use self::websocket_transceiver::WebSocketTransceiver;
+pub use self::websocket_transceiver::{ClientId, WebSocketTransceiver};
 
 mod usb_transceiver;
 pub use self::usb_transceiver::UsbTransceiver;
+
+pub mod frame;
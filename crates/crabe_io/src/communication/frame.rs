@@ -0,0 +1,196 @@
+use crate::constant::BASE_MAX_PAYLOAD;
+use crc::{Crc, CRC_16_IBM_3740};
+
+/// Byte marking the start of a frame, used to resynchronise on the stream after an error.
+pub const FRAME_START: u8 = 0xA5;
+/// Size of the header of a frame: start byte, version, kind and payload length.
+const HEADER_SIZE: usize = 5;
+/// Size of the CRC16 ending a frame.
+const CHECKSUM_SIZE: usize = 2;
+
+const CRC16: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_3740);
+
+/// The kind of a frame exchanged with the base station.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Announces the range of protocol versions supported by the sender, as two bytes
+    /// `[min, max]` in the payload.
+    Hello = 0,
+    /// Carries an encoded protobuf message (`PcToBase` or `BaseToPc`).
+    Data = 1,
+}
+
+impl TryFrom<u8> for FrameKind {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FrameKind::Hello),
+            1 => Ok(FrameKind::Data),
+            _ => Err(value),
+        }
+    }
+}
+
+/// A frame of the serial protocol spoken with the base station. On the wire, a frame is:
+///
+/// | start (`0xA5`) | version | kind | length (u16 LE) | payload | CRC16 (u16 LE) |
+///
+/// The CRC16 (CCITT) covers everything from the version to the end of the payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub version: u8,
+    pub kind: FrameKind,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    /// Encodes the frame into the bytes sent on the wire.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the payload is bigger than `BASE_MAX_PAYLOAD`.
+    pub fn encode(&self) -> Vec<u8> {
+        assert!(
+            self.payload.len() <= BASE_MAX_PAYLOAD,
+            "frame payload too big"
+        );
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.payload.len() + CHECKSUM_SIZE);
+        bytes.push(FRAME_START);
+        bytes.push(self.version);
+        bytes.push(self.kind as u8);
+        bytes.extend_from_slice(&(self.payload.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.payload);
+        let checksum = CRC16.checksum(&bytes[1..]);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+}
+
+/// Counters of the errors met on the serial link with the base station.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkErrors {
+    /// Bytes skipped while looking for the start of a frame.
+    pub skipped_bytes: u64,
+    /// Frames with an unknown kind or a length over `BASE_MAX_PAYLOAD`.
+    pub malformed: u64,
+    /// Frames whose CRC16 doesn't match their content.
+    pub checksum: u64,
+    /// Frames using a protocol version other than the negotiated one.
+    pub version: u64,
+    /// Frame payloads that couldn't be decoded as a protobuf message.
+    pub decode: u64,
+    /// Packets that couldn't be sent, because they are too big or the port failed.
+    pub send: u64,
+}
+
+/// The `FrameDecoder` struct extracts the frames from the bytes received on the serial link. The
+/// bytes of an incomplete frame are kept until the rest is received, and the decoder skips to
+/// the next start byte when a frame is corrupted.
+#[derive(Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    errors: LinkErrors,
+}
+
+impl FrameDecoder {
+    /// Appends the bytes received on the link.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the errors met since the creation of the decoder.
+    pub fn errors(&self) -> LinkErrors {
+        self.errors
+    }
+
+    /// Returns the next complete and valid frame of the received bytes, if any.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            let start = self
+                .buffer
+                .iter()
+                .position(|&b| b == FRAME_START)
+                .unwrap_or(self.buffer.len());
+            if start > 0 {
+                self.errors.skipped_bytes += start as u64;
+                self.buffer.drain(..start);
+            }
+            if self.buffer.len() < HEADER_SIZE {
+                return None;
+            }
+
+            let length = u16::from_le_bytes([self.buffer[3], self.buffer[4]]) as usize;
+            let kind = match FrameKind::try_from(self.buffer[2]) {
+                Ok(kind) if length <= BASE_MAX_PAYLOAD => kind,
+                _ => {
+                    self.errors.malformed += 1;
+                    self.buffer.drain(..1);
+                    continue;
+                }
+            };
+
+            let end = HEADER_SIZE + length + CHECKSUM_SIZE;
+            if self.buffer.len() < end {
+                return None;
+            }
+            let checksum = u16::from_le_bytes([self.buffer[end - 2], self.buffer[end - 1]]);
+            if CRC16.checksum(&self.buffer[1..end - CHECKSUM_SIZE]) != checksum {
+                self.errors.checksum += 1;
+                self.buffer.drain(..1);
+                continue;
+            }
+
+            let frame = Frame {
+                version: self.buffer[1],
+                kind,
+                payload: self.buffer[HEADER_SIZE..end - CHECKSUM_SIZE].to_vec(),
+            };
+            self.buffer.drain(..end);
+            return Some(frame);
+        }
+    }
+}
+
+/// Returns the highest protocol version in both ranges, if they overlap.
+pub fn negotiate_version(ours: (u8, u8), theirs: (u8, u8)) -> Option<u8> {
+    let version = ours.1.min(theirs.1);
+    (version >= ours.0.max(theirs.0)).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoder_resynchronises_after_corruption() {
+        let frame = Frame {
+            version: 1,
+            kind: FrameKind::Data,
+            payload: (0..=255).cycle().take(600).collect(),
+        };
+        let bytes = frame.encode();
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 0xFF;
+
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&[0x00, 0x42]);
+        decoder.push(&corrupted);
+        decoder.push(&bytes[..50]);
+        assert_eq!(decoder.next_frame(), None);
+        decoder.push(&bytes[50..]);
+        assert_eq!(decoder.next_frame(), Some(frame));
+        assert_eq!(decoder.next_frame(), None);
+
+        let errors = decoder.errors();
+        assert!(errors.checksum >= 1);
+        assert!(errors.skipped_bytes >= 2);
+    }
+
+    #[test]
+    fn versions_are_negotiated() {
+        assert_eq!(negotiate_version((1, 3), (2, 5)), Some(3));
+        assert_eq!(negotiate_version((1, 1), (1, 2)), Some(1));
+        assert_eq!(negotiate_version((1, 1), (2, 3)), None);
+    }
+}
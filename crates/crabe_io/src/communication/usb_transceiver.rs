@@ -1,38 +1,180 @@
-use crate::constant::BUFFER_SIZE;
-use log::{debug, error};
+use crate::communication::frame::{negotiate_version, Frame, FrameDecoder, FrameKind, LinkErrors};
+use crate::constant::{
+    BASE_HELLO_PERIOD, BASE_MAX_PAYLOAD, BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION,
+};
+use log::{debug, error, info};
 use serialport::SerialPort;
-use std::time::Duration;
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
 
+/// A struct that provides bidirectional communication with the base station over a serial port.
+/// The packets are sent in frames checked by a CRC16, see `Frame`.
 pub struct UsbTransceiver {
     port: Box<dyn SerialPort>,
-    buffer: [u8; BUFFER_SIZE],
+    decoder: FrameDecoder,
+    /// The protocol version agreed with the base station, once it answered our hello.
+    version: Option<u8>,
+    /// When the last hello was sent.
+    last_hello: Instant,
+    errors: LinkErrors,
 }
 
 impl UsbTransceiver {
+    /// Opens the serial port and announces the supported protocol versions to the base station.
+    /// Until it answers, the packets are sent with `BASE_PROTOCOL_VERSION` and the hello is sent
+    /// again every `BASE_HELLO_PERIOD`, since the base station may start after us or miss it.
     pub fn new(port: &str, baud: u32) -> Result<Self, serialport::Error> {
         let port = serialport::new(port, baud)
             .timeout(Duration::from_millis(1))
             .open()?;
 
-        let buffer = [0u8; BUFFER_SIZE];
+        let mut transceiver = Self {
+            port,
+            decoder: Default::default(),
+            version: None,
+            last_hello: Instant::now(),
+            errors: Default::default(),
+        };
+        transceiver.send_hello();
+        Ok(transceiver)
+    }
+
+    fn send_hello(&mut self) {
+        self.last_hello = Instant::now();
+        self.write_frame(Frame {
+            version: BASE_PROTOCOL_VERSION,
+            kind: FrameKind::Hello,
+            payload: vec![BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION],
+        });
+    }
+
+    /// Returns the protocol version used on the link.
+    pub fn version(&self) -> u8 {
+        self.version.unwrap_or(BASE_PROTOCOL_VERSION)
+    }
+
+    /// Returns the errors met on the link since it was opened.
+    pub fn errors(&self) -> LinkErrors {
+        let decoder = self.decoder.errors();
+        LinkErrors {
+            skipped_bytes: decoder.skipped_bytes,
+            malformed: decoder.malformed,
+            checksum: decoder.checksum,
+            ..self.errors
+        }
+    }
 
-        Ok(Self { port, buffer })
+    fn write_frame(&mut self, frame: Frame) {
+        if let Err(e) = self.port.write_all(&frame.encode()) {
+            self.errors.send += 1;
+            error!("send error: {}", e);
+        }
     }
 
+    /// Sends a `T` packet in a single frame. Packets bigger than `BASE_MAX_PAYLOAD` are dropped,
+    /// they must be split by the caller. While the base station has not answered, the hello is
+    /// sent again before the packet when `BASE_HELLO_PERIOD` elapsed.
     pub fn send<T: prost::Message + Default>(&mut self, packet: T) {
-        let mut buf = Vec::new();
-        buf.reserve(packet.encoded_len() + 1);
-        buf.push(packet.encoded_len() as u8);
-        if let Err(err) = packet.encode(&mut buf) {
-            error!("Cannot encode the packet, {}", err);
+        if self.version.is_none() && self.last_hello.elapsed() >= BASE_HELLO_PERIOD {
+            self.send_hello();
         }
+        if packet.encoded_len() > BASE_MAX_PAYLOAD {
+            self.errors.send += 1;
+            error!(
+                "Cannot send a packet of {} bytes, the maximum is {}",
+                packet.encoded_len(),
+                BASE_MAX_PAYLOAD
+            );
+            return;
+        }
+
+        self.write_frame(Frame {
+            version: self.version(),
+            kind: FrameKind::Data,
+            payload: packet.encode_to_vec(),
+        });
+        debug!("sent: {:?}", packet);
+    }
 
-        match self.port.write(&buf[0..packet.encoded_len() + 1]) {
-            Ok(_v) => {
-                debug!("sent: {:?}", packet);
+    /// Reads the bytes already received on the serial port, without blocking, and decodes the
+    /// packets of the complete frames among them. The bytes of an incomplete frame are kept for
+    /// the next call.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `U`: The type of packet to decode. It must implement the `prost::Message` and `Default`
+    ///   traits, and should be a struct generated by protobuf files using `prost`.
+    ///
+    /// # Returns
+    ///
+    /// The decoded packets, in the order they were received.
+    pub fn receive<U: prost::Message + Default>(&mut self) -> Vec<U> {
+        self.read_available();
+
+        let mut packets = vec![];
+        while let Some(frame) = self.decoder.next_frame() {
+            match frame.kind {
+                FrameKind::Hello => self.on_hello(&frame.payload),
+                FrameKind::Data if frame.version != self.version() => {
+                    self.errors.version += 1;
+                    debug!("Ignoring a frame of protocol version {}", frame.version);
+                }
+                FrameKind::Data => match U::decode(frame.payload.as_slice()) {
+                    Ok(packet) => {
+                        debug!("received: {:?}", packet);
+                        packets.push(packet);
+                    }
+                    Err(e) => {
+                        self.errors.decode += 1;
+                        error!("Decoding of the received packet failed: {}", e);
+                    }
+                },
             }
+        }
+        packets
+    }
+
+    fn read_available(&mut self) {
+        let available = match self.port.bytes_to_read() {
+            Ok(available) => available as usize,
             Err(e) => {
-                error!("send error: {}", e);
+                error!("Cannot query the serial port: {}", e);
+                return;
+            }
+        };
+        if available == 0 {
+            return;
+        }
+
+        let mut bytes = vec![0; available];
+        match self.port.read(&mut bytes) {
+            Ok(read) => self.decoder.push(&bytes[..read]),
+            Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {}
+            Err(e) => error!("receive error: {}", e),
+        }
+    }
+
+    fn on_hello(&mut self, payload: &[u8]) {
+        let [min, max] = payload else {
+            self.errors.version += 1;
+            error!("Invalid hello from the base station: {:?}", payload);
+            return;
+        };
+
+        match negotiate_version(
+            (BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION),
+            (*min, *max),
+        ) {
+            Some(version) => {
+                info!("Base station protocol version {}", version);
+                self.version = Some(version);
+            }
+            None => {
+                self.errors.version += 1;
+                error!(
+                    "The base station supports the protocol versions {} to {}, we support {} to {}",
+                    min, max, BASE_MIN_PROTOCOL_VERSION, BASE_PROTOCOL_VERSION
+                );
             }
         }
     }
@@ -1,96 +1,351 @@
+use crate::constant::{ACCUMULATED_QUEUE_SIZE, BUFFER_SIZE, REPLY_QUEUE_SIZE};
 use flume::{unbounded, Receiver, Sender};
-use futures_util::StreamExt;
-use log::{error, info};
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info, warn};
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::runtime::Runtime;
-use tokio::task::JoinSet;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
 use tungstenite::Message;
 
-pub struct WebSocketThread<RX, TX> {
+/// The last message sent to the clients, shared by all of them.
+type Latest = Option<Arc<serde_json::Value>>;
+
+/// The identifier of a connected client, which is its address.
+pub type ClientId = SocketAddr;
+
+/// A request of a client, or the reason why it could not be parsed.
+pub type ClientMessage<RX> = (ClientId, Result<RX, String>);
+
+/// The queues of the serialized replies to the connected clients.
+type Replies = Arc<Mutex<HashMap<ClientId, mpsc::Sender<String>>>>;
+
+/// The items of the accumulated topics not sent yet to each connected client, by topic.
+type Accumulated =
+    Arc<Mutex<HashMap<ClientId, HashMap<&'static str, VecDeque<serde_json::Value>>>>>;
+
+/// Moves the items of the accumulated `topics` of `message` into the queue of each client,
+/// dropping the oldest ones beyond `ACCUMULATED_QUEUE_SIZE`.
+fn accumulate(accumulated: &Accumulated, topics: &[&'static str], message: &mut serde_json::Value) {
+    let serde_json::Value::Object(fields) = message else {
+        return;
+    };
+    let mut accumulated = accumulated.lock().expect("accumulated poisoned");
+    for topic in topics {
+        let Some(serde_json::Value::Array(items)) = fields.get_mut(*topic) else {
+            continue;
+        };
+        let items = std::mem::take(items);
+        for queues in accumulated.values_mut() {
+            let queue = queues.entry(*topic).or_default();
+            queue.extend(items.iter().cloned());
+            let overflow = queue.len().saturating_sub(ACCUMULATED_QUEUE_SIZE);
+            queue.drain(..overflow);
+        }
+    }
+}
+
+/// The `Subscription` struct holds what a client wants to receive. A client changes it by
+/// sending `{"requestType": "subscribe", "payload": {"topics": [...], "rate": ...}}`.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    /// The top-level fields of the messages to send to the client, all of them when `None`.
+    pub topics: Option<Vec<String>>,
+    /// The maximum number of messages per second sent to the client, unlimited when `None`.
+    pub rate: Option<f64>,
+}
+
+impl Subscription {
+    /// Returns the minimum time between two messages sent to the client.
+    fn period(&self) -> Duration {
+        self.rate
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| Duration::from_secs_f64(1.0 / rate))
+            .unwrap_or_default()
+    }
+
+    /// Keeps the topics of the client in a serialized message.
+    fn filter(&self, mut message: serde_json::Value) -> serde_json::Value {
+        if let (Some(topics), serde_json::Value::Object(fields)) = (&self.topics, &mut message) {
+            fields.retain(|field, _| topics.contains(field));
+        }
+        message
+    }
+}
+
+/// The requests handled by the websocket itself, the other ones are handed to the system.
+#[derive(Deserialize)]
+#[serde(tag = "requestType", content = "payload", rename_all = "camelCase")]
+enum ClientRequest {
+    Subscribe(Subscription),
+}
+
+pub struct WebSocketThread<RX> {
     addr: SocketAddr,
-    rx: Receiver<TX>,
-    tx: Sender<RX>,
+    broadcast: Arc<watch::Sender<Latest>>,
+    replies: Replies,
+    accumulated: Accumulated,
+    tx: Sender<ClientMessage<RX>>,
+    /// The HTML page served to the plain HTTP requests, if any.
+    page: Option<&'static str>,
     runtime: Runtime,
 }
 
-impl<RX: Send + DeserializeOwned, TX: Send + Serialize> WebSocketThread<RX, TX> {
-    fn new(addr: SocketAddr, rx: Receiver<TX>, tx: Sender<RX>) -> Self {
+/// Returns the length of the HTTP request header at the beginning of `data`, if it is complete.
+fn header_length(data: &[u8]) -> Option<usize> {
+    data.windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+/// Returns whether the HTTP request header `header` asks for a websocket.
+fn is_websocket_upgrade(header: &[u8]) -> bool {
+    String::from_utf8_lossy(header)
+        .to_ascii_lowercase()
+        .lines()
+        .any(|line| line.starts_with("upgrade:") && line.contains("websocket"))
+}
+
+impl<RX: Send + DeserializeOwned> WebSocketThread<RX> {
+    fn new(
+        addr: SocketAddr,
+        broadcast: Arc<watch::Sender<Latest>>,
+        replies: Replies,
+        accumulated: Accumulated,
+        tx: Sender<ClientMessage<RX>>,
+        page: Option<&'static str>,
+    ) -> Self {
         let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
             .unwrap();
         Self {
             addr,
-            rx,
+            broadcast,
+            replies,
+            accumulated,
             tx,
+            page,
             runtime,
         }
     }
 
+    /// Waits for the HTTP request header of a new connection without consuming it, so that it
+    /// can still be read by the websocket handshake. Returns `None` if the header is not valid.
+    async fn peek_header(stream: &TcpStream) -> std::io::Result<Option<Vec<u8>>> {
+        let mut buffer = [0u8; BUFFER_SIZE];
+        // The header usually comes in one packet, a few retries cover the other cases.
+        for _ in 0..100 {
+            let len = stream.peek(&mut buffer).await?;
+            if let Some(header_len) = header_length(&buffer[..len]) {
+                return Ok(Some(buffer[..header_len].to_vec()));
+            }
+            if len == 0 || len == buffer.len() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(None)
+    }
+
+    /// Answers a plain HTTP request with the page, and closes the connection.
+    async fn serve_page(
+        mut stream: TcpStream,
+        addr: SocketAddr,
+        header: &[u8],
+        page: &str,
+    ) -> std::io::Result<()> {
+        let mut request = vec![0u8; header.len()];
+        stream.read_exact(&mut request).await?;
+        let path = String::from_utf8_lossy(header)
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string();
+        info!("HTTP request from {} for {}", addr, path);
+
+        let response = if path == "/" || path == "/index.html" {
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                page.len(),
+                page
+            )
+        } else {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        };
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    /// Sends the replies to the client as soon as they are available, and the latest message
+    /// whenever there is a new one, at most at the rate of its subscription. The messages sent
+    /// while the client is busy are dropped, only the latest one is sent, along with all the
+    /// items of the accumulated topics since the previous one.
+    async fn send_loop(
+        mut outgoing: impl futures_util::Sink<Message, Error = tungstenite::Error> + Unpin,
+        mut latest: watch::Receiver<Latest>,
+        mut replies: mpsc::Receiver<String>,
+        subscription: Arc<Mutex<Subscription>>,
+        addr: ClientId,
+        accumulated: Accumulated,
+    ) -> Result<(), tungstenite::Error> {
+        let mut next_send = Instant::now();
+        let mut pending = false;
+        loop {
+            tokio::select! {
+                reply = replies.recv() => match reply {
+                    Some(reply) => outgoing.send(Message::text(reply)).await?,
+                    None => break,
+                },
+                changed = latest.changed(), if !pending => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    pending = true;
+                }
+                _ = tokio::time::sleep_until(next_send), if pending => {
+                    pending = false;
+                    let subscription = subscription.lock().expect("subscription poisoned").clone();
+                    next_send = Instant::now() + subscription.period();
+                    let Some(message) = latest.borrow_and_update().clone() else {
+                        continue;
+                    };
+                    let mut message = subscription.filter((*message).clone());
+                    let topics = accumulated
+                        .lock()
+                        .expect("accumulated poisoned")
+                        .get_mut(&addr)
+                        .map(std::mem::take)
+                        .unwrap_or_default();
+                    if let serde_json::Value::Object(fields) = &mut message {
+                        for (topic, items) in topics {
+                            if let Some(field) = fields.get_mut(topic) {
+                                *field = serde_json::Value::Array(items.into());
+                            }
+                        }
+                    }
+                    outgoing.send(Message::text(message.to_string())).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
     async fn handle_connection(
         raw_stream: TcpStream,
         addr: SocketAddr,
-        rx: Receiver<TX>,
-        tx: Sender<RX>,
+        latest: watch::Receiver<Latest>,
+        replies: Replies,
+        accumulated: Accumulated,
+        tx: Sender<ClientMessage<RX>>,
+        page: Option<&'static str>,
     ) -> Result<(), tungstenite::Error> {
         info!("Incoming TCP connection from: {}", addr);
+        if let Some(page) = page {
+            match Self::peek_header(&raw_stream).await? {
+                Some(header) if !is_websocket_upgrade(&header) => {
+                    return Ok(Self::serve_page(raw_stream, addr, &header, page).await?);
+                }
+                _ => {}
+            }
+        }
+
         let ws_stream = tokio_tungstenite::accept_async(raw_stream).await?;
         info!("WebSocket connection established: {}", addr);
 
-        let (outgoing, incoming) = ws_stream.split();
-        let incoming_fut = incoming
-            .filter_map(|x| async move {
-                if let Ok(Message::Text(m)) = x {
-                    if let Ok(req) = serde_json::from_str::<RX>(&m) {
-                        return Some(Ok(req));
+        let (reply_tx, reply_rx) = mpsc::channel(REPLY_QUEUE_SIZE);
+        replies
+            .lock()
+            .expect("replies poisoned")
+            .insert(addr, reply_tx);
+        accumulated
+            .lock()
+            .expect("accumulated poisoned")
+            .insert(addr, HashMap::new());
+
+        let subscription = Arc::new(Mutex::new(Subscription::default()));
+        let (outgoing, mut incoming) = ws_stream.split();
+        let client_subscription = subscription.clone();
+        let incoming_fut = async move {
+            while let Some(Ok(message)) = incoming.next().await {
+                let Message::Text(m) = message else {
+                    continue;
+                };
+                if let Ok(ClientRequest::Subscribe(new)) = serde_json::from_str(&m) {
+                    info!("{} subscribed to {:?}", addr, new);
+                    *client_subscription.lock().expect("subscription poisoned") = new;
+                } else {
+                    let request = serde_json::from_str::<RX>(&m).map_err(|e| {
+                        warn!("Invalid request from {}: {}", addr, m);
+                        e.to_string()
+                    });
+                    if tx.send((addr, request)).is_err() {
+                        break;
                     }
                 }
+            }
+        };
+        let outgoing_fut = Self::send_loop(
+            outgoing,
+            latest,
+            reply_rx,
+            subscription,
+            addr,
+            accumulated.clone(),
+        );
 
-                None
-            })
-            .forward(tx.sink());
-        let outgoing_fut = rx
-            .stream()
-            .map(|m| Ok(Message::text(serde_json::to_string(&m).unwrap())))
-            .forward(outgoing);
-        let (_incoming_res, _outgoing_res) = tokio::join!(incoming_fut, outgoing_fut);
-
+        // The client is disconnected as soon as one of the directions is closed.
+        let result = tokio::select! {
+            _ = incoming_fut => Ok(()),
+            res = outgoing_fut => res,
+        };
+        replies.lock().expect("replies poisoned").remove(&addr);
+        accumulated
+            .lock()
+            .expect("accumulated poisoned")
+            .remove(&addr);
         info!("{} disconnected", &addr);
 
-        Ok(())
+        result
     }
 }
 
-impl<RX: Send + DeserializeOwned + 'static, TX: Send + Serialize + 'static>
-    WebSocketThread<RX, TX>
-{
+impl<RX: Send + DeserializeOwned + 'static> WebSocketThread<RX> {
     fn run(&mut self, cancellation: Receiver<()>) {
         let future = async {
             let try_socket = TcpListener::bind(&self.addr).await;
             let listener = try_socket.expect("Failed to bind");
             info!("Listening on: {}", self.addr);
             let receive = async {
-                let mut join_set = JoinSet::new();
                 while let Ok((stream, addr)) = listener.accept().await {
-                    join_set.spawn(Self::handle_connection(
+                    // The connections are dropped with the runtime when the thread stops.
+                    let connection = Self::handle_connection(
                         stream,
                         addr,
-                        self.rx.clone(),
+                        self.broadcast.subscribe(),
+                        self.replies.clone(),
+                        self.accumulated.clone(),
                         self.tx.clone(),
-                    ));
-                }
-
-                while let Some(res) = join_set.join_next().await {
-                    if let Err(join_error) = res {
-                        error!("Join error: #{join_error}")
-                    } else if let Ok(Err(connection_error)) = res {
-                        error!("Connection error: #{connection_error}")
-                    }
+                        self.page,
+                    );
+                    tokio::spawn(async move {
+                        if let Err(connection_error) = connection.await {
+                            error!("Connection error: #{connection_error}")
+                        }
+                    });
                 }
             };
 
@@ -103,44 +358,102 @@ impl<RX: Send + DeserializeOwned + 'static, TX: Send + Serialize + 'static>
     }
 }
 
+/// The `WebSocketTransceiver` struct broadcasts messages to every connected websocket client and
+/// collects their requests, which can be replied to.
+///
+/// Each client only gets the latest message: a slow client skips the messages it could not
+/// send in time, so sending never blocks. The arrays of the accumulated topics (e.g. the plot
+/// samples) are not skipped: their items are queued for each client, up to
+/// `ACCUMULATED_QUEUE_SIZE`, and sent with its next message.
 pub struct WebSocketTransceiver<RX, TX> {
-    rx: Receiver<RX>,
-    tx: Sender<TX>,
+    rx: Receiver<ClientMessage<RX>>,
+    broadcast: Arc<watch::Sender<Latest>>,
+    replies: Replies,
+    accumulated: Accumulated,
+    accumulated_topics: &'static [&'static str],
     handle: JoinHandle<()>,
     cancellation_tx: Sender<()>,
+    message: PhantomData<TX>,
 }
 
-impl<RX: DeserializeOwned + Send + 'static, TX: Serialize + Send + 'static>
+impl<RX: DeserializeOwned + Send + 'static, TX: Serialize + Send + Sync + 'static>
     WebSocketTransceiver<RX, TX>
 {
-    pub fn spawn(addr: SocketAddr) -> Self {
+    /// Spawns the thread listening on `addr`. When a `page` is given, it is served to the plain
+    /// HTTP requests on the same address, so that a browser can open it. The
+    /// `accumulated_topics` are the top-level arrays of the messages whose items must all reach
+    /// the clients.
+    pub fn spawn(
+        addr: SocketAddr,
+        page: Option<&'static str>,
+        accumulated_topics: &'static [&'static str],
+    ) -> Self {
         let (task_tx, transceiver_rx) = unbounded();
-        let (transceiver_tx, task_rx) = unbounded();
+        let (broadcast, _) = watch::channel(None);
+        let broadcast = Arc::new(broadcast);
+        let replies = Replies::default();
+        let accumulated = Accumulated::default();
         let (cancellation_tx, cancellation_rx) = unbounded();
 
-        let mut websocket_thread = WebSocketThread::new(addr, task_rx, task_tx);
+        let mut websocket_thread = WebSocketThread::new(
+            addr,
+            broadcast.clone(),
+            replies.clone(),
+            accumulated.clone(),
+            task_tx,
+            page,
+        );
         let handle = thread::spawn(move || {
             websocket_thread.run(cancellation_rx);
         });
 
         Self {
             rx: transceiver_rx,
-            tx: transceiver_tx,
+            broadcast,
+            replies,
+            accumulated,
+            accumulated_topics,
             cancellation_tx,
             handle,
+            message: PhantomData,
         }
     }
 
+    /// Returns whether at least one client is connected.
+    pub fn has_clients(&self) -> bool {
+        self.broadcast.receiver_count() > 0
+    }
+
+    /// Sends `msg` to all the connected clients, replacing the message not sent yet. The items
+    /// of its accumulated topics are queued for each client instead.
     pub fn send(&mut self, msg: TX) {
-        self.tx
-            .send(msg)
-            .unwrap_or_else(|x| error!("Send error: #{x}"));
+        let mut message = match serde_json::to_value(&msg) {
+            Ok(message) => message,
+            Err(e) => return error!("Cannot serialize the message: {}", e),
+        };
+        accumulate(&self.accumulated, self.accumulated_topics, &mut message);
+        self.broadcast.send_replace(Some(Arc::new(message)));
     }
 
-    pub fn receive(&mut self) -> Option<RX> {
+    /// Returns the next request received from a client, if any.
+    pub fn receive(&mut self) -> Option<ClientMessage<RX>> {
         self.rx.try_recv().ok()
     }
 
+    /// Sends `reply` to the client `client` only. The reply is dropped if the client is gone
+    /// or has too many replies waiting.
+    pub fn reply(&self, client: ClientId, reply: &impl Serialize) {
+        let reply = match serde_json::to_string(reply) {
+            Ok(reply) => reply,
+            Err(e) => return error!("Cannot serialize the reply: {}", e),
+        };
+        if let Some(queue) = self.replies.lock().expect("replies poisoned").get(&client) {
+            if queue.try_send(reply).is_err() {
+                warn!("Reply to {} dropped, the client is not reading", client);
+            }
+        }
+    }
+
     pub fn close(self) {
         self.cancellation_tx
             .send(())
@@ -148,3 +461,100 @@ impl<RX: DeserializeOwned + Send + 'static, TX: Serialize + Send + 'static>
         self.handle.join().unwrap_or_else(|_e| error!("Join error"));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn subscriptions_filter_the_topics() {
+        let message = json!({"world": {}, "annotations": [], "plots": [], "metrics": {}});
+        let subscription: Subscription =
+            serde_json::from_value(json!({"topics": ["world", "plots"], "rate": 10.0})).unwrap();
+        assert_eq!(
+            subscription.filter(message.clone()),
+            json!({"world": {}, "plots": []})
+        );
+        assert_eq!(subscription.period(), Duration::from_millis(100));
+        assert_eq!(Subscription::default().filter(message.clone()), message);
+        assert_eq!(Subscription::default().period(), Duration::ZERO);
+    }
+
+    #[test]
+    fn websocket_handshakes_are_told_apart() {
+        let request = b"GET / HTTP/1.1\r\nHost: localhost:10400\r\nUpgrade: websocket\r\n\r\n";
+        let len = header_length(request).unwrap();
+        assert_eq!(len, request.len());
+        assert!(is_websocket_upgrade(&request[..len]));
+
+        let request = b"GET / HTTP/1.1\r\nHost: localhost:10400\r\n\r\n";
+        assert!(!is_websocket_upgrade(request));
+        assert_eq!(header_length(b"GET / HTTP/1.1\r\nHost: local"), None);
+    }
+
+    #[test]
+    fn rate_limited_clients_receive_all_the_accumulated_items() {
+        // The time is paused, it only moves forward when all the tasks wait.
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .start_paused(true)
+            .build()
+            .unwrap();
+        let client: ClientId = "127.0.0.1:10400".parse().unwrap();
+        let accumulated = Accumulated::default();
+        accumulated.lock().unwrap().insert(client, HashMap::new());
+        let subscription = Arc::new(Mutex::new(Subscription {
+            topics: None,
+            rate: Some(10.0),
+        }));
+
+        const COUNT: usize = 20;
+        let sent = runtime.block_on(async {
+            let (broadcast, latest) = watch::channel(None);
+            let (_reply_tx, reply_rx) = mpsc::channel(REPLY_QUEUE_SIZE);
+            let (sent_tx, mut sent_rx) = mpsc::unbounded_channel();
+            let outgoing = Box::pin(futures_util::sink::unfold(
+                sent_tx,
+                |sent_tx, message: Message| async move {
+                    sent_tx.send(message).unwrap();
+                    Ok::<_, tungstenite::Error>(sent_tx)
+                },
+            ));
+            let send_loop = tokio::spawn(WebSocketThread::<serde_json::Value>::send_loop(
+                outgoing,
+                latest,
+                reply_rx,
+                subscription,
+                client,
+                accumulated.clone(),
+            ));
+
+            for i in 0..COUNT {
+                let mut message = json!({"world": i, "plots": [i]});
+                accumulate(&accumulated, &["plots"], &mut message);
+                broadcast.send_replace(Some(Arc::new(message)));
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            // The last message is still sent once its period elapsed.
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            drop(broadcast);
+            send_loop.await.unwrap().unwrap();
+
+            let mut sent = vec![];
+            while let Ok(Message::Text(text)) = sent_rx.try_recv() {
+                sent.push(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+            }
+            sent
+        });
+
+        let plots: Vec<_> = sent
+            .iter()
+            .flat_map(|message| message["plots"].as_array().unwrap().clone())
+            .map(|plot| plot.as_u64().unwrap() as usize)
+            .collect();
+        assert_eq!(plots, (0..COUNT).collect::<Vec<_>>());
+        assert!(sent.len() < COUNT);
+        assert_eq!(sent.last().unwrap()["world"], json!(COUNT - 1));
+    }
+}
@@ -4,5 +4,27 @@
 pub const BUFFER_SIZE: usize = 4096;
 pub const VISION_PORT_REAL: u16 = 10006;
 pub const VISION_PORT_SIM: u16 = 10020;
+pub const SIM_CONTROL_PORT: u16 = 10300;
 pub const SIM_PORT_BLUE: u16 = 10301;
 pub const SIM_PORT_YELLOW: u16 = 10302;
+/// Maximum number of replies waiting to be sent to a tool client.
+pub const REPLY_QUEUE_SIZE: usize = 64;
+/// Maximum number of items of an accumulated topic (e.g. the plot samples) waiting to be sent to
+/// a tool client.
+pub const ACCUMULATED_QUEUE_SIZE: usize = 4096;
+/// Version of the serial protocol spoken with the base station.
+pub const BASE_PROTOCOL_VERSION: u8 = 1;
+/// Oldest version of the serial protocol still supported.
+pub const BASE_MIN_PROTOCOL_VERSION: u8 = 1;
+/// Maximum size of the payload of a frame exchanged with the base station.
+pub const BASE_MAX_PAYLOAD: usize = 1024;
+/// Maximum number of robot commands sent to the base station in a single frame.
+pub const BASE_MAX_COMMANDS: usize = 16;
+/// Period at which the hello is sent again until the base station answers it.
+pub const BASE_HELLO_PERIOD: std::time::Duration = std::time::Duration::from_millis(500);
+/// Port of the synchronous simulation API, on which the simulator is stepped on request.
+pub const SIM_SYNC_PORT: u16 = 10303;
+/// Maximum number of vision frames of the synchronous simulator waiting to be read by the input.
+pub const SIM_SYNC_VISION_QUEUE_SIZE: usize = 64;
+/// Maximum time to wait for the response of the simulator to a synchronous step.
+pub const SIM_SYNC_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
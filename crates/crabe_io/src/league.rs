@@ -4,6 +4,8 @@ pub mod game_controller;
 
 pub mod real;
 pub mod simulator;
+/// This module provides a struct for receiving the tracked frames of an external tracker.
+pub mod tracker;
 /// This module provides an interface and a struct for communicating with SSL
 /// Vision or the Simulator vision module.
 pub mod vision;
@@ -1,6 +1,7 @@
 use crate::communication::MulticastUDPReceiver;
 use crate::league::game_controller::GameControllerConfig;
 use crate::pipeline::input::ReceiverTask;
+use crate::recorder::{MessageType, Recorder};
 use crabe_framework::data::input::InboundData;
 use crabe_protocol::protobuf::game_controller_packet::Referee;
 use log::{error, info};
@@ -20,7 +21,7 @@ pub struct GameController {
 }
 
 impl GameController {
-    pub fn with_config(cli: GameControllerConfig) -> Self {
+    pub fn with_config(cli: GameControllerConfig, recorder: &Recorder) -> Self {
         let (tx_gc, rx_gc) = mpsc::channel::<Referee>();
         let ipv4 = Ipv4Addr::from_str(cli.gc_ip.as_str())
             .expect("Failed to create an ipv4 address with the ip");
@@ -28,10 +29,12 @@ impl GameController {
             MulticastUDPReceiver::new(ipv4, cli.gc_port).expect("Failed to create GC receiver");
         let running = Arc::new(AtomicBool::new(true));
         let running_clone = Arc::clone(&running);
+        let recorder = recorder.clone();
 
         let handle = thread::spawn(move || {
             while running_clone.load(Ordering::Relaxed) {
-                if let Some(packet) = gc.receive() {
+                if let Some(packet) = gc.receive::<Referee>() {
+                    recorder.record(MessageType::Refbox2013, &packet);
                     if let Err(e) = tx_gc.send(packet) {
                         error!("Error sending GameController packet: {:?}", e);
                     }
@@ -1,4 +1,5 @@
 mod config;
 mod task;
 pub use config::RealConfig;
+pub(crate) use task::prepare_packet;
 pub use task::Real;
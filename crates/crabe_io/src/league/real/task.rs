@@ -1,16 +1,44 @@
 use crate::league::real::RealConfig;
-use log::error;
+use log::warn;
 
 use crabe_framework::constant::MAX_ID_ROBOTS;
-use crabe_framework::data::output::{Command, CommandMap, FeedbackMap, Kick};
+use crabe_framework::data::output::{Command, CommandMap, Feedback, FeedbackMap, Kick};
 
-use crabe_protocol::protobuf::robot_packet::{BaseCommand, Kicker, PcToBase};
+use crabe_protocol::protobuf::robot_packet::{BaseCommand, BaseToPc, Kicker, PcToBase};
 
+use crate::communication::frame::LinkErrors;
 use crate::communication::UsbTransceiver;
+use crate::constant::BASE_MAX_COMMANDS;
 use crate::pipeline::output::CommandSenderTask;
 
+/// Converts the commands into the packet sent to the base station.
+pub(crate) fn prepare_packet(commands: impl Iterator<Item = (u8, Command)>) -> PcToBase {
+    let mut packet = PcToBase::default();
+    for (id, command) in commands {
+        let (kicker_cmd, kick_power) = match command.kick {
+            None => (Kicker::NoKick, 0.0_f32),
+            Some(Kick::StraightKick { power }) => (Kicker::Flat, power),
+            Some(Kick::ChipKick { power }) => (Kicker::Chip, power),
+        };
+
+        packet.commands.push(BaseCommand {
+            robot_id: id as u32,
+            normal_velocity: command.forward_velocity,
+            tangential_velocity: command.left_velocity,
+            angular_velocity: command.angular_velocity,
+            kick: kicker_cmd.into(),
+            kick_power,
+            charge: command.charge,
+            dribbler: command.dribbler,
+        });
+    }
+    packet
+}
+
 pub struct Real {
     usb: UsbTransceiver,
+    /// The errors of the link already reported.
+    reported_errors: LinkErrors,
 }
 
 impl Real {
@@ -18,45 +46,58 @@ impl Real {
         let usb = UsbTransceiver::new(&usb_config.usb_port, usb_config.usb_baud)
             .expect("Failed to create usb transceiver");
 
-        Self { usb }
+        Self {
+            usb,
+            reported_errors: Default::default(),
+        }
     }
 
-    fn prepare_packet(&mut self, commands: impl Iterator<Item = (u8, Command)>) -> PcToBase {
-        let mut packet = PcToBase::default();
-        for (id, command) in commands {
-            let (kicker_cmd, kick_power) = match command.kick {
-                None => (Kicker::NoKick, 0.0_f32),
-                Some(Kick::StraightKick { power }) => (Kicker::Flat, power),
-                Some(Kick::ChipKick { power }) => (Kicker::Chip, power),
-            };
-
-            packet.commands.push(BaseCommand {
-                robot_id: id as u32,
-                normal_velocity: command.forward_velocity,
-                tangential_velocity: command.left_velocity,
-                angular_velocity: command.angular_velocity,
-                kick: kicker_cmd.into(),
-                kick_power,
-                charge: command.charge,
-                dribbler: command.dribbler,
-            });
+    /// Reads the feedback frames sent by the base station since the last step. When several
+    /// frames give the feedback of the same robot, the latest one is kept.
+    fn fetch(&mut self) -> FeedbackMap {
+        let mut feedback_map: FeedbackMap = Default::default();
+        for packet in self.usb.receive::<BaseToPc>() {
+            for feedback in packet.feedbacks {
+                feedback_map.insert(
+                    feedback.robot_id,
+                    Feedback {
+                        has_ball: feedback.ir,
+                        voltage: Some(feedback.voltage),
+                        motor_speeds: Some([
+                            feedback.motor_1_speed,
+                            feedback.motor_2_speed,
+                            feedback.motor_3_speed,
+                            feedback.motor_4_speed,
+                        ]),
+                    },
+                );
+            }
         }
-        packet
+
+        feedback_map
     }
 }
 
 impl CommandSenderTask for Real {
     fn step(&mut self, commands: CommandMap) -> FeedbackMap {
-        dbg!(&commands);
-        if commands.len() > 16 {
-            error!("Capacity oversize for the commands !");
-            Default::default()
+        // The base station handles a limited number of commands per frame. A frame is sent even
+        // without commands, the base station only sends the feedback while it receives frames.
+        let commands: Vec<_> = commands.into_iter().collect();
+        if commands.is_empty() {
+            self.usb.send(PcToBase::default());
+        }
+        for chunk in commands.chunks(BASE_MAX_COMMANDS) {
+            let packet = prepare_packet(chunk.iter().copied());
+            self.usb.send(packet);
         }
 
-        let packet = self.prepare_packet(commands.into_iter());
-        self.usb.send(packet);
-
-        Default::default()
+        let feedback = self.fetch();
+        let errors = self.usb.errors();
+        if errors != self.reported_errors {
+            warn!("Errors on the link with the base station: {:?}", errors);
+            self.reported_errors = errors;
+        }
+        feedback
     }
 
     fn close(&mut self) {
@@ -1,2 +1,4 @@
 pub mod config;
+pub mod control;
+pub mod synchronous;
 pub mod task;
@@ -1,6 +1,10 @@
+use crate::constant::SIM_SYNC_PORT;
 use clap::Args;
 #[derive(Args)]
 pub struct SimulatorConfig {
     #[arg(long)]
     pub simulator_port: Option<u16>,
+    /// Port of the synchronous API of the simulator, used with `--synchronous`.
+    #[arg(long, default_value_t = SIM_SYNC_PORT)]
+    pub sync_port: u16,
 }
@@ -0,0 +1,220 @@
+use crate::communication::UDPTransceiver;
+use crabe_framework::constant::MAX_ID_ROBOTS;
+use crabe_framework::data::tool::SimulatorCommand;
+use crabe_framework::data::world::TeamColor;
+use crabe_framework::robot_profile::RobotProfile;
+use crabe_protocol::protobuf::simulation_packet::{
+    RobotId, RobotLimits, RobotSpecs, RobotWheelAngles, SimulatorCommand as SimulatorCommandPacket,
+    SimulatorConfig, SimulatorControl, SimulatorError, SimulatorResponse, Team, TeleportBall,
+    TeleportRobot,
+};
+use nalgebra::{Point2, Vector2};
+use std::net::Ipv4Addr;
+
+/// The `SimulatorControlClient` struct controls the simulation through the control port of the
+/// simulator: it places the ball and the robots, removes robots and changes the speed of the
+/// simulation. It can be used on its own, to set up a situation before running a test.
+///
+/// The positions and orientations are expressed in the raw frame of the simulator, not in the
+/// `World` frame.
+pub struct SimulatorControlClient {
+    socket: UDPTransceiver,
+}
+
+impl SimulatorControlClient {
+    /// Creates a client of the simulator running on `ip`, with the control port `port`
+    /// (`SIM_CONTROL_PORT` by default).
+    pub fn new(ip: Ipv4Addr, port: u16) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            socket: UDPTransceiver::new(ip, port)?,
+        })
+    }
+
+    /// Sends the command `command` to the simulator.
+    pub fn send(&self, command: &SimulatorCommand) {
+        self.socket.send(SimulatorCommandPacket {
+            control: Some(prepare_control_packet(command)),
+            config: None,
+        });
+    }
+
+    /// Moves the ball to `position` (in meters) with the velocity `velocity` (in m.s-1).
+    pub fn teleport_ball(&self, position: Point2<f64>, velocity: Vector2<f64>) {
+        self.send(&SimulatorCommand::TeleportBall { position, velocity });
+    }
+
+    /// Moves the robot `id` of the team `color` to `position` (in meters) with the orientation
+    /// `orientation` (in radians), adding it to the field if needed.
+    pub fn teleport_robot(
+        &self,
+        id: u8,
+        color: TeamColor,
+        position: Point2<f64>,
+        orientation: f64,
+    ) {
+        self.send(&SimulatorCommand::TeleportRobot {
+            id,
+            color,
+            position,
+            orientation,
+            present: true,
+        });
+    }
+
+    /// Removes the robot `id` of the team `color` from the field.
+    pub fn remove_robot(&self, id: u8, color: TeamColor) {
+        self.send(&SimulatorCommand::TeleportRobot {
+            id,
+            color,
+            position: Point2::origin(),
+            orientation: 0.0,
+            present: false,
+        });
+    }
+
+    /// Changes the speed of the simulation, 1.0 being real time.
+    pub fn set_speed(&self, speed: f64) {
+        self.send(&SimulatorCommand::SetSpeed(speed));
+    }
+
+    /// Replaces the default robot model of the simulator by the profile `profile` for all the
+    /// robots of the team `color`.
+    pub fn configure_robots(&self, color: TeamColor, profile: &RobotProfile) {
+        self.socket.send(SimulatorCommandPacket {
+            control: None,
+            config: Some(prepare_robots_config(color, profile)),
+        });
+    }
+
+    /// Returns the errors reported by the simulator since the last call, such as the use of
+    /// unsupported features.
+    pub fn receive_errors(&mut self) -> Vec<SimulatorError> {
+        let mut errors = vec![];
+        while let Some(response) = self.socket.receive::<SimulatorResponse>() {
+            errors.extend(response.errors);
+        }
+        errors
+    }
+}
+
+fn robot_id(id: u8, color: TeamColor) -> RobotId {
+    let team = match color {
+        TeamColor::Yellow => Team::Yellow,
+        TeamColor::Blue => Team::Blue,
+    };
+    RobotId {
+        id: Some(id as u32),
+        team: Some(team as i32),
+    }
+}
+
+fn prepare_robot_specs(id: u8, color: TeamColor, profile: &RobotProfile) -> RobotSpecs {
+    let limits = &profile.limits;
+    let angles = &profile.wheel_angles;
+    RobotSpecs {
+        id: robot_id(id, color),
+        radius: Some(profile.radius as f32),
+        height: Some(profile.height as f32),
+        mass: Some(profile.mass as f32),
+        max_linear_kick_speed: Some(profile.kick.max_straight_speed as f32),
+        max_chip_kick_speed: Some(profile.kick.max_chip_speed as f32),
+        center_to_dribbler: Some(profile.center_to_dribbler as f32),
+        limits: Some(RobotLimits {
+            acc_speedup_absolute_max: Some(limits.max_linear_acceleration as f32),
+            acc_speedup_angular_max: Some(limits.max_angular_acceleration as f32),
+            acc_brake_absolute_max: Some(limits.max_linear_deceleration as f32),
+            acc_brake_angular_max: Some(limits.max_angular_deceleration as f32),
+            vel_absolute_max: Some(limits.max_linear_velocity as f32),
+            vel_angular_max: Some(limits.max_angular_velocity as f32),
+        }),
+        wheel_angles: Some(RobotWheelAngles {
+            front_right: angles.front_right.to_radians() as f32,
+            back_right: angles.back_right.to_radians() as f32,
+            back_left: angles.back_left.to_radians() as f32,
+            front_left: angles.front_left.to_radians() as f32,
+        }),
+        custom: None,
+    }
+}
+
+/// Returns the configuration of the simulator giving the profile `profile` to all the robots of
+/// the team `color`.
+pub(crate) fn prepare_robots_config(color: TeamColor, profile: &RobotProfile) -> SimulatorConfig {
+    SimulatorConfig {
+        robot_specs: (0..=MAX_ID_ROBOTS as u8)
+            .map(|id| prepare_robot_specs(id, color, profile))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn prepare_control_packet(command: &SimulatorCommand) -> SimulatorControl {
+    let mut control = SimulatorControl::default();
+    match *command {
+        SimulatorCommand::TeleportBall { position, velocity } => {
+            control.teleport_ball = Some(TeleportBall {
+                x: Some(position.x as f32),
+                y: Some(position.y as f32),
+                z: Some(0.0),
+                vx: Some(velocity.x as f32),
+                vy: Some(velocity.y as f32),
+                vz: Some(0.0),
+                teleport_safely: Some(true),
+                roll: Some(true),
+            });
+        }
+        SimulatorCommand::TeleportRobot {
+            id,
+            color,
+            position,
+            orientation,
+            present,
+        } => {
+            control.teleport_robot.push(TeleportRobot {
+                id: robot_id(id, color),
+                x: Some(position.x as f32),
+                y: Some(position.y as f32),
+                orientation: Some(orientation as f32),
+                v_x: Some(0.0),
+                v_y: Some(0.0),
+                v_angular: Some(0.0),
+                present: Some(present),
+            });
+        }
+        SimulatorCommand::SetSpeed(speed) => {
+            control.simulation_speed = Some(speed as f32);
+        }
+    }
+    control
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_packets_are_prepared() {
+        let control = prepare_control_packet(&SimulatorCommand::SetSpeed(2.0));
+        assert_eq!(control.simulation_speed, Some(2.0));
+        assert!(control.teleport_ball.is_none());
+
+        let control = prepare_control_packet(&SimulatorCommand::TeleportRobot {
+            id: 3,
+            color: TeamColor::Yellow,
+            position: Point2::new(1.0, -2.0),
+            orientation: 0.5,
+            present: false,
+        });
+        let robot = &control.teleport_robot[0];
+        assert_eq!(robot.id.id, Some(3));
+        assert_eq!(robot.id.team, Some(Team::Yellow as i32));
+        assert_eq!((robot.x, robot.y), (Some(1.0), Some(-2.0)));
+        assert_eq!(robot.present, Some(false));
+
+        let specs = prepare_robot_specs(2, TeamColor::Blue, &RobotProfile::default());
+        assert_eq!(specs.id.team, Some(Team::Blue as i32));
+        assert_eq!(specs.limits.unwrap().vel_absolute_max, Some(2.0));
+        let front_right = specs.wheel_angles.unwrap().front_right;
+        assert!((front_right - std::f32::consts::FRAC_PI_3).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::constant::{BUFFER_SIZE, SIM_SYNC_TIMEOUT, SIM_SYNC_VISION_QUEUE_SIZE};
+use crate::league::simulator::config::SimulatorConfig;
+use crate::league::simulator::control::{prepare_control_packet, prepare_robots_config};
+use crate::league::simulator::task::{feedback_map, log_error, prepare_packet};
+use crate::pipeline::input::ReceiverTask;
+use crate::pipeline::output::CommandSenderTask;
+use crate::recorder::{MessageType, Recorder};
+use chrono::{DateTime, Utc};
+use crabe_framework::clock::SharedClock;
+use crabe_framework::config::CommonConfig;
+use crabe_framework::constant::MAX_ID_ROBOTS;
+use crabe_framework::data::input::InboundData;
+use crabe_framework::data::output::{CommandMap, FeedbackMap};
+use crabe_framework::data::tool::SimulatorCommand;
+use crabe_framework::data::world::TeamColor;
+use crabe_protocol::protobuf::simulation_packet::{
+    SimulationSyncRequest, SimulationSyncResponse, SimulatorCommand as SimulatorCommandPacket,
+    SslDetectionFrame,
+};
+use crabe_protocol::protobuf::vision_packet;
+use crabe_protocol::protobuf::vision_packet::SslWrapperPacket;
+use flume::{bounded, Receiver, Sender, TrySendError};
+use log::error;
+use prost::Message;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+
+/// The sending end of the channel of the vision frames, held by the `SynchronousSimulator`.
+pub type VisionSender = Sender<SslWrapperPacket>;
+/// The receiving end of the channel of the vision frames, held by the `SynchronousVision`.
+pub type VisionReceiver = Receiver<SslWrapperPacket>;
+
+/// Creates the channel through which the vision frames received by the `SynchronousSimulator`
+/// output reach the `SynchronousVision` input.
+pub fn vision_channel() -> (VisionSender, VisionReceiver) {
+    bounded(SIM_SYNC_VISION_QUEUE_SIZE)
+}
+
+/// Converts a detection frame of the simulation protocol into the one of SSL-Vision, which is
+/// the same message.
+fn to_vision_frame(frame: SslDetectionFrame) -> Option<vision_packet::SslDetectionFrame> {
+    vision_packet::SslDetectionFrame::decode(frame.encode_to_vec().as_slice())
+        .map_err(|e| error!("Invalid detection frame from the simulator: {}", e))
+        .ok()
+}
+
+/// Adds the control command `command` to the commands `pending` sent with the next step. The
+/// latest teleportation of the ball and speed replace the previous ones, the teleportations of
+/// the robots are all sent.
+fn merge_control(pending: &mut SimulatorCommandPacket, command: &SimulatorCommand) {
+    let control = prepare_control_packet(command);
+    let pending = pending.control.get_or_insert_with(Default::default);
+    if control.teleport_ball.is_some() {
+        pending.teleport_ball = control.teleport_ball;
+    }
+    if control.simulation_speed.is_some() {
+        pending.simulation_speed = control.simulation_speed;
+    }
+    pending.teleport_robot.extend(control.teleport_robot);
+}
+
+/// Takes the commands `pending` to send with the next step, if there are any.
+fn take_pending(pending: &mut SimulatorCommandPacket) -> Option<SimulatorCommandPacket> {
+    let pending = std::mem::take(pending);
+    (pending != Default::default()).then_some(pending)
+}
+
+/// The `SynchronousSimulator` struct drives a simulator through its synchronous API: at each
+/// iteration of the system, it sends the commands of the robots and asks the simulator to step
+/// by the time elapsed on the clock since the previous iteration, then waits for the vision and
+/// the feedback of the robots after the step. The vision is read by the `SynchronousVision`
+/// input at the next iteration.
+///
+/// The messages are exchanged over UDP, one request and one response per datagram.
+pub struct SynchronousSimulator {
+    socket: UdpSocket,
+    buffer: [u8; BUFFER_SIZE],
+    clock: SharedClock,
+    /// The time of the previous step, none before the first one.
+    last_step: Option<DateTime<Utc>>,
+    /// The commands sent to the simulator with the next step.
+    pending: SimulatorCommandPacket,
+    vision: Sender<SslWrapperPacket>,
+    recorder: Recorder,
+}
+
+impl SynchronousSimulator {
+    pub fn with_config(
+        simulator_cfg: SimulatorConfig,
+        common_cfg: &CommonConfig,
+        clock: &SharedClock,
+        vision: &VisionSender,
+        recorder: &Recorder,
+    ) -> Self {
+        let socket = UdpSocket::bind(SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0))
+            .and_then(|socket| {
+                socket.connect(SocketAddrV4::new(
+                    Ipv4Addr::LOCALHOST,
+                    simulator_cfg.sync_port,
+                ))?;
+                socket.set_read_timeout(Some(SIM_SYNC_TIMEOUT))?;
+                Ok(socket)
+            })
+            .expect("Failed to setup the synchronous simulator");
+
+        let color = if common_cfg.yellow {
+            TeamColor::Yellow
+        } else {
+            TeamColor::Blue
+        };
+
+        Self {
+            socket,
+            buffer: [0u8; BUFFER_SIZE],
+            clock: clock.clone(),
+            last_step: None,
+            // Our robot model is sent with the first step.
+            pending: SimulatorCommandPacket {
+                control: None,
+                config: Some(prepare_robots_config(color, &common_cfg.robot_profile())),
+            },
+            vision: vision.clone(),
+            recorder: recorder.clone(),
+        }
+    }
+
+    /// Drops the responses that arrived after the timeout of the previous steps.
+    fn drop_late_responses(&mut self) {
+        if self.socket.set_nonblocking(true).is_err() {
+            return;
+        }
+        while self.socket.recv(&mut self.buffer).is_ok() {}
+        if let Err(e) = self.socket.set_nonblocking(false) {
+            error!(
+                "Cannot set the synchronous simulator socket to blocking: {}",
+                e
+            );
+        }
+    }
+
+    /// Sends the request `request` and waits for its response.
+    fn exchange(&mut self, request: SimulationSyncRequest) -> Option<SimulationSyncResponse> {
+        self.drop_late_responses();
+        if let Err(e) = self.socket.send(&request.encode_to_vec()) {
+            error!("Failed to send the step to the simulator: {}", e);
+            return None;
+        }
+
+        match self.socket.recv(&mut self.buffer) {
+            Ok(size) => SimulationSyncResponse::decode(&self.buffer[..size])
+                .map_err(|e| error!("Decoding of the simulator response failed: {}", e))
+                .ok(),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                error!(
+                    "The simulator didn't answer the step in {:?}",
+                    SIM_SYNC_TIMEOUT
+                );
+                None
+            }
+            Err(e) => {
+                error!("Failed to receive the response of the simulator: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl CommandSenderTask for SynchronousSimulator {
+    fn step(&mut self, commands: CommandMap) -> FeedbackMap {
+        let now = self.clock.now();
+        let sim_step = self
+            .last_step
+            .map(|last| (now - last).to_std().unwrap_or_default().as_secs_f32())
+            .unwrap_or(0.0);
+        self.last_step = Some(now);
+
+        let request = SimulationSyncRequest {
+            sim_step: Some(sim_step),
+            simulator_command: take_pending(&mut self.pending),
+            robot_control: Some(prepare_packet(commands.into_iter())),
+        };
+        let Some(response) = self.exchange(request) else {
+            return Default::default();
+        };
+
+        for frame in response.detection.into_iter().filter_map(to_vision_frame) {
+            let packet = SslWrapperPacket {
+                detection: Some(frame),
+                geometry: None,
+            };
+            self.recorder.record(MessageType::Vision2014, &packet);
+            // The input reads the frames at each step, they pile up only when it is not running.
+            match self.vision.try_send(packet) {
+                Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+                Err(TrySendError::Full(_)) => error!("Dropping a vision frame, nobody reads it"),
+            }
+        }
+
+        response
+            .robot_control_response
+            .map(|response| {
+                response.errors.iter().for_each(log_error);
+                feedback_map(response)
+            })
+            .unwrap_or_default()
+    }
+
+    /// The commands are sent with the next step, see `merge_control`.
+    fn control_simulator(&mut self, command: &SimulatorCommand) {
+        merge_control(&mut self.pending, command);
+    }
+
+    fn close(&mut self) {
+        let mut commands: CommandMap = Default::default();
+        for id in 0..=MAX_ID_ROBOTS {
+            commands.insert(id as u8, Default::default());
+        }
+
+        self.step(commands);
+    }
+}
+
+/// The `SynchronousVision` struct receives the vision frames that the `SynchronousSimulator`
+/// output got from the simulator at the previous iteration.
+pub struct SynchronousVision {
+    rx_vision: VisionReceiver,
+}
+
+impl SynchronousVision {
+    pub fn new(rx_vision: &VisionReceiver) -> Self {
+        Self {
+            rx_vision: rx_vision.clone(),
+        }
+    }
+}
+
+impl ReceiverTask for SynchronousVision {
+    fn fetch(&mut self, input: &mut InboundData) {
+        input.vision_packet.extend(self.rx_vision.try_iter());
+    }
+
+    fn close(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crabe_framework::robot_profile::RobotProfile;
+    use crabe_protocol::protobuf::simulation_packet::{SslDetectionBall, SslDetectionRobot};
+    use nalgebra::{Point2, Vector2};
+
+    #[test]
+    fn detection_frames_are_converted() {
+        let frame = SslDetectionFrame {
+            frame_number: 7,
+            t_capture: 1.5,
+            camera_id: 2,
+            balls: vec![SslDetectionBall {
+                x: 100.0,
+                y: -200.0,
+                ..Default::default()
+            }],
+            robots_blue: vec![SslDetectionRobot {
+                robot_id: Some(3),
+                x: 50.0,
+                orientation: Some(1.0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let frame = to_vision_frame(frame).unwrap();
+        assert_eq!((frame.frame_number, frame.camera_id), (7, 2));
+        assert_eq!(frame.t_capture, 1.5);
+        assert_eq!((frame.balls[0].x, frame.balls[0].y), (100.0, -200.0));
+        assert_eq!(frame.robots_blue[0].robot_id, Some(3));
+        assert_eq!(frame.robots_blue[0].orientation, Some(1.0));
+        assert!(frame.robots_yellow.is_empty());
+    }
+
+    #[test]
+    fn control_commands_are_merged_until_the_next_step() {
+        let mut pending = SimulatorCommandPacket {
+            control: None,
+            config: Some(prepare_robots_config(
+                TeamColor::Blue,
+                &RobotProfile::default(),
+            )),
+        };
+        let teleport_ball = |x| SimulatorCommand::TeleportBall {
+            position: Point2::new(x, 0.0),
+            velocity: Vector2::zeros(),
+        };
+        let teleport_robot = |id| SimulatorCommand::TeleportRobot {
+            id,
+            color: TeamColor::Blue,
+            position: Point2::origin(),
+            orientation: 0.0,
+            present: true,
+        };
+        merge_control(&mut pending, &teleport_ball(1.0));
+        merge_control(&mut pending, &SimulatorCommand::SetSpeed(2.0));
+        merge_control(&mut pending, &teleport_robot(1));
+        merge_control(&mut pending, &teleport_ball(2.0));
+        merge_control(&mut pending, &SimulatorCommand::SetSpeed(0.5));
+        merge_control(&mut pending, &teleport_robot(2));
+
+        let first = take_pending(&mut pending).unwrap();
+        assert!(first.config.is_some());
+        let control = first.control.unwrap();
+        assert_eq!(control.teleport_ball.unwrap().x, Some(2.0));
+        assert_eq!(control.simulation_speed, Some(0.5));
+        let ids: Vec<_> = control.teleport_robot.iter().map(|r| r.id.id).collect();
+        assert_eq!(ids, [Some(1), Some(2)]);
+
+        // The robot config is only sent with the first step.
+        assert_eq!(take_pending(&mut pending), None);
+        merge_control(&mut pending, &SimulatorCommand::SetSpeed(1.0));
+        let next = take_pending(&mut pending).unwrap();
+        assert!(next.config.is_none());
+        assert_eq!(next.control.unwrap().simulation_speed, Some(1.0));
+    }
+}
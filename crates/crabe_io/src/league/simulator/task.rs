@@ -1,18 +1,21 @@
 use crate::communication::UDPTransceiver;
 
 use crate::league::simulator::config::SimulatorConfig;
+use crate::league::simulator::control::SimulatorControlClient;
 
 use crabe_framework::data::output::{Command, CommandMap, Feedback, FeedbackMap, Kick};
+use crabe_framework::data::tool::SimulatorCommand;
+use crabe_framework::data::world::TeamColor;
 
 use crabe_protocol::protobuf::simulation_packet::{
     robot_move_command, MoveLocalVelocity, RobotCommand, RobotControl, RobotControlResponse,
-    RobotMoveCommand,
+    RobotMoveCommand, SimulatorError,
 };
-use log::debug;
+use log::{debug, error};
 
 use std::net::Ipv4Addr;
 
-use crate::constant::{SIM_PORT_BLUE, SIM_PORT_YELLOW};
+use crate::constant::{SIM_CONTROL_PORT, SIM_PORT_BLUE, SIM_PORT_YELLOW};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::constant::MAX_ID_ROBOTS;
 
@@ -20,6 +23,7 @@ use crate::pipeline::output::CommandSenderTask;
 
 pub struct Simulator {
     socket: UDPTransceiver,
+    control: SimulatorControlClient,
 }
 
 impl Simulator {
@@ -35,71 +39,101 @@ impl Simulator {
 
         let socket =
             UDPTransceiver::new(Ipv4Addr::LOCALHOST, port).expect("Failed to setup simulator");
+        let control = SimulatorControlClient::new(Ipv4Addr::LOCALHOST, SIM_CONTROL_PORT)
+            .expect("Failed to setup simulator control");
 
-        Self { socket }
-    }
-
-    fn prepare_packet(&self, commands: impl Iterator<Item = (u8, Command)>) -> RobotControl {
-        let mut packet = RobotControl::default();
-
-        for (id, command) in commands {
-            let (kick_speed, kick_angle) = match &command.kick {
-                None => (0.0, 0.0),
-                Some(Kick::StraightKick { power }) => (*power, 0.0),
-                Some(Kick::ChipKick { power }) => (*power, 45.0),
-            };
-
-            let robot_command = RobotCommand {
-                id: id as u32,
-                move_command: Some(RobotMoveCommand {
-                    command: Some(robot_move_command::Command::LocalVelocity(
-                        MoveLocalVelocity {
-                            forward: command.forward_velocity,
-                            left: command.left_velocity,
-                            angular: command.angular_velocity,
-                        },
-                    )),
-                }),
-                kick_speed: Some(kick_speed),
-                kick_angle: Some(kick_angle),
-                dribbler_speed: Some(command.dribbler),
-            };
-            packet.robot_commands.push(robot_command);
-        }
+        let color = if common_cfg.yellow {
+            TeamColor::Yellow
+        } else {
+            TeamColor::Blue
+        };
+        control.configure_robots(color, &common_cfg.robot_profile());
 
-        packet
+        Self { socket, control }
     }
 
     fn fetch(&mut self) -> FeedbackMap {
-        let mut feedback_map: FeedbackMap = Default::default();
-        if let Some(packet) = self.socket.receive::<RobotControlResponse>() {
-            for robot_feedback in packet.feedback {
-                debug!(
-                    "assigned feedback {:?} to robot #{}",
-                    robot_feedback, robot_feedback.id
-                );
-
-                feedback_map.insert(
-                    robot_feedback.id,
-                    Feedback {
-                        has_ball: robot_feedback.dribbler_ball_contact(),
-                        voltage: Default::default(),
+        self.socket
+            .receive::<RobotControlResponse>()
+            .map(feedback_map)
+            .unwrap_or_default()
+    }
+}
+
+/// Converts the commands of the robots into a packet of the simulation protocol.
+pub(crate) fn prepare_packet(commands: impl Iterator<Item = (u8, Command)>) -> RobotControl {
+    let mut packet = RobotControl::default();
+
+    for (id, command) in commands {
+        let (kick_speed, kick_angle) = match &command.kick {
+            None => (0.0, 0.0),
+            Some(Kick::StraightKick { power }) => (*power, 0.0),
+            Some(Kick::ChipKick { power }) => (*power, 45.0),
+        };
+
+        let robot_command = RobotCommand {
+            id: id as u32,
+            move_command: Some(RobotMoveCommand {
+                command: Some(robot_move_command::Command::LocalVelocity(
+                    MoveLocalVelocity {
+                        forward: command.forward_velocity,
+                        left: command.left_velocity,
+                        angular: command.angular_velocity,
                     },
-                );
-            }
-        }
+                )),
+            }),
+            kick_speed: Some(kick_speed),
+            kick_angle: Some(kick_angle),
+            dribbler_speed: Some(command.dribbler),
+        };
+        packet.robot_commands.push(robot_command);
+    }
+
+    packet
+}
+
+/// Logs an error reported by the simulator.
+pub(crate) fn log_error(e: &SimulatorError) {
+    error!(
+        "Simulator error {}: {}",
+        e.code.as_deref().unwrap_or_default(),
+        e.message.as_deref().unwrap_or_default()
+    );
+}
 
-        feedback_map
+/// Extracts the feedback of the robots from a response of the simulator.
+pub(crate) fn feedback_map(response: RobotControlResponse) -> FeedbackMap {
+    let mut feedback_map: FeedbackMap = Default::default();
+    for robot_feedback in response.feedback {
+        debug!(
+            "assigned feedback {:?} to robot #{}",
+            robot_feedback, robot_feedback.id
+        );
+
+        feedback_map.insert(
+            robot_feedback.id,
+            Feedback {
+                has_ball: robot_feedback.dribbler_ball_contact(),
+                ..Default::default()
+            },
+        );
     }
+
+    feedback_map
 }
 
 impl CommandSenderTask for Simulator {
     fn step(&mut self, commands: CommandMap) -> FeedbackMap {
-        let packet = self.prepare_packet(commands.into_iter());
+        let packet = prepare_packet(commands.into_iter());
         self.socket.send(packet);
+        self.control.receive_errors().iter().for_each(log_error);
         self.fetch()
     }
 
+    fn control_simulator(&mut self, command: &SimulatorCommand) {
+        self.control.send(command);
+    }
+
     fn close(&mut self) {
         let mut commands: CommandMap = Default::default();
         for id in 0..MAX_ID_ROBOTS {
@@ -0,0 +1,5 @@
+mod config;
+pub use config::TrackerConfig;
+
+mod tracker_thread;
+pub use tracker_thread::Tracker;
@@ -0,0 +1,68 @@
+use crate::communication::MulticastUDPReceiver;
+use crate::league::tracker::TrackerConfig;
+use crate::pipeline::input::ReceiverTask;
+use crate::recorder::{MessageType, Recorder};
+use crabe_framework::data::input::InboundData;
+use crabe_protocol::protobuf::tracked_vision_packet::TrackerWrapperPacket;
+use log::{error, info};
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::thread::JoinHandle;
+
+/// The `Tracker` struct receives the tracked frames of an external tracker, which filters the
+/// vision on its own and estimates the velocities of the robots and the ball.
+pub struct Tracker {
+    rx_tracker: Receiver<TrackerWrapperPacket>,
+    handle: Option<JoinHandle<()>>,
+    running: Arc<AtomicBool>,
+}
+
+impl Tracker {
+    pub fn with_config(cli: TrackerConfig, recorder: &Recorder) -> Self {
+        let (tx_tracker, rx_tracker) = mpsc::channel::<TrackerWrapperPacket>();
+        let ipv4 = Ipv4Addr::from_str(cli.tracker_ip.as_str())
+            .expect("Failed to create an ipv4 address with the ip");
+        let mut tracker = MulticastUDPReceiver::new(ipv4, cli.tracker_port)
+            .expect("Failed to create tracker receiver");
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+        let recorder = recorder.clone();
+
+        let handle = thread::spawn(move || {
+            while running_clone.load(Ordering::Relaxed) {
+                if let Some(packet) = tracker.receive::<TrackerWrapperPacket>() {
+                    recorder.record(MessageType::VisionTracker2020, &packet);
+                    if let Err(e) = tx_tracker.send(packet) {
+                        error!("Error sending Tracker packet: {:?}", e);
+                    }
+                }
+            }
+        });
+
+        Self {
+            rx_tracker,
+            handle: Some(handle),
+            running,
+        }
+    }
+}
+
+impl ReceiverTask for Tracker {
+    fn fetch(&mut self, input: &mut InboundData) {
+        input.tracker_packet.extend(self.rx_tracker.try_iter());
+    }
+
+    fn close(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            match handle.join() {
+                Ok(_) => info!("Tracker Thread finished successfully"),
+                Err(e) => println!("Tracker thread finished with an error: {:?}", e),
+            }
+        }
+    }
+}
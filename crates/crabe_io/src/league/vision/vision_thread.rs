@@ -2,6 +2,7 @@ use crate::communication::MulticastUDPReceiver;
 use crate::constant::{VISION_PORT_REAL, VISION_PORT_SIM};
 use crate::league::vision::VisionConfig;
 use crate::pipeline::input::ReceiverTask;
+use crate::recorder::{MessageType, Recorder};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::data::input::InboundData;
 use crabe_protocol::protobuf::vision_packet::SslWrapperPacket;
@@ -22,7 +23,11 @@ pub struct Vision {
 }
 
 impl Vision {
-    pub fn with_config(vision_cfg: VisionConfig, common_cfg: &CommonConfig) -> Self {
+    pub fn with_config(
+        vision_cfg: VisionConfig,
+        common_cfg: &CommonConfig,
+        recorder: &Recorder,
+    ) -> Self {
         let port = if let Some(port) = vision_cfg.vision_port {
             port
         } else if common_cfg.real {
@@ -39,10 +44,12 @@ impl Vision {
 
         let running = Arc::new(AtomicBool::new(true));
         let running_clone = Arc::clone(&running);
+        let recorder = recorder.clone();
 
         let handle = thread::spawn(move || {
             while running_clone.load(Ordering::Relaxed) {
-                if let Some(packet) = vision.receive() {
+                if let Some(packet) = vision.receive::<SslWrapperPacket>() {
+                    recorder.record(MessageType::Vision2014, &packet);
                     if let Err(e) = tx_vision.send(packet) {
                         error!("Error sending Vision packet: {:?}", e);
                     }
@@ -20,5 +20,7 @@ pub mod constant;
 pub mod league;
 
 pub mod pipeline;
+/// The `recorder` module reads and writes the standard SSL log files.
+pub mod recorder;
 pub mod tool;
 // pub mod serial;
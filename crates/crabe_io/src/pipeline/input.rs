@@ -1,16 +1,30 @@
 use crate::league::game_controller::{GameController, GameControllerConfig};
+use crate::league::simulator::synchronous::{SynchronousVision, VisionReceiver};
+use crate::league::tracker::{Tracker, TrackerConfig};
 use crate::league::vision::{Vision, VisionConfig};
+use crate::recorder::Recorder;
 use clap::Args;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::component::{Component, InputComponent};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::data::input::InboundData;
 use crabe_framework::data::output::FeedbackMap;
+use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct InputConfig {
     #[arg(long)]
     gc: bool,
 
+    /// Receives the tracked frames of an external tracker, in addition to the vision.
+    #[arg(long)]
+    tracker: bool,
+
+    /// Records the inbound packets, our commands and the feedback of our robots to this SSL
+    /// log file, or to a new file named after the date in this directory (see `Recorder`).
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
     #[command(flatten)]
     #[command(next_help_heading = "Vision")]
     pub vision_cfg: VisionConfig,
@@ -18,6 +32,10 @@ pub struct InputConfig {
     #[command(flatten)]
     #[command(next_help_heading = "Game Controller")]
     pub gc_cfg: GameControllerConfig,
+
+    #[command(flatten)]
+    #[command(next_help_heading = "Tracker")]
+    pub tracker_cfg: TrackerConfig,
 }
 
 pub trait ReceiverTask {
@@ -27,33 +45,66 @@ pub trait ReceiverTask {
 
 pub struct InputPipeline {
     receivers: Vec<Box<dyn ReceiverTask>>,
+    recorder: Recorder,
 }
 
 impl InputPipeline {
-    pub fn with_config(input_cfg: InputConfig, common_cfg: &CommonConfig) -> Self {
-        let mut tasks: Vec<Box<dyn ReceiverTask>> = vec![Box::new(Vision::with_config(
-            input_cfg.vision_cfg,
-            common_cfg,
-        ))];
+    pub fn with_config(
+        input_cfg: InputConfig,
+        common_cfg: &CommonConfig,
+        _clock: &SharedClock,
+        sync_vision: &VisionReceiver,
+        recorder: &Recorder,
+    ) -> Self {
+        // The synchronous simulator gives the vision back with each step.
+        let mut tasks: Vec<Box<dyn ReceiverTask>> = if common_cfg.synchronous {
+            vec![Box::new(SynchronousVision::new(sync_vision))]
+        } else {
+            vec![Box::new(Vision::with_config(
+                input_cfg.vision_cfg,
+                common_cfg,
+                recorder,
+            ))]
+        };
 
         if input_cfg.gc {
-            tasks.push(Box::new(GameController::with_config(input_cfg.gc_cfg)));
+            tasks.push(Box::new(GameController::with_config(
+                input_cfg.gc_cfg,
+                recorder,
+            )));
         }
 
-        Self { receivers: tasks }
+        if input_cfg.tracker {
+            tasks.push(Box::new(Tracker::with_config(
+                input_cfg.tracker_cfg,
+                recorder,
+            )));
+        }
+
+        Self {
+            receivers: tasks,
+            recorder: recorder.clone(),
+        }
     }
 }
 
 impl Component for InputPipeline {
     fn close(mut self) {
         self.receivers.drain(..).for_each(|mut x| x.close());
+        self.recorder.flush();
     }
 }
 
 impl InputComponent for InputPipeline {
-    fn step(&mut self, _feedback: &mut FeedbackMap) -> InboundData {
-        let mut data = InboundData::default();
+    fn step(&mut self, feedback: &mut FeedbackMap) -> InboundData {
+        let mut data = InboundData {
+            feedback: std::mem::take(feedback),
+            ..Default::default()
+        };
         self.receivers.iter_mut().for_each(|x| x.fetch(&mut data));
+
+        // The log is flushed at each step, to be replayable even if the system is killed.
+        self.recorder.flush();
         data
     }
 }
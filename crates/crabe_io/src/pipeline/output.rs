@@ -1,13 +1,17 @@
+use crabe_framework::clock::SharedClock;
 use crabe_framework::component::{Component, OutputComponent};
 use crabe_framework::config::CommonConfig;
 
 use crabe_framework::data::output::{CommandMap, FeedbackMap};
-use crabe_framework::data::tool::ToolCommands;
+use crabe_framework::data::tool::{SimulatorCommand, ToolCommand, ToolCommands};
 
 use crate::league::real::{Real, RealConfig};
 use crate::league::simulator::config::SimulatorConfig;
+use crate::league::simulator::synchronous::{SynchronousSimulator, VisionSender};
 use crate::league::simulator::task::Simulator;
+use crate::recorder::Recorder;
 use clap::Args;
+use log::warn;
 
 #[derive(Args)]
 pub struct OutputConfig {
@@ -23,21 +27,44 @@ pub struct OutputConfig {
 pub trait CommandSenderTask {
     fn step(&mut self, commands: CommandMap) -> FeedbackMap;
     fn close(&mut self);
+
+    /// Sends a control command to the simulator, if the task drives a simulator.
+    fn control_simulator(&mut self, _command: &SimulatorCommand) {
+        warn!("Simulator commands are ignored with the real robots");
+    }
 }
 
 pub struct OutputPipeline {
     command_task: Box<dyn CommandSenderTask>,
+    recorder: Recorder,
 }
 
 impl OutputPipeline {
-    pub fn with_config(output_cfg: OutputConfig, common_cfg: &CommonConfig) -> OutputPipeline {
+    pub fn with_config(
+        output_cfg: OutputConfig,
+        common_cfg: &CommonConfig,
+        clock: &SharedClock,
+        sync_vision: &VisionSender,
+        recorder: &Recorder,
+    ) -> OutputPipeline {
         let command_task: Box<dyn CommandSenderTask> = if common_cfg.real {
             Box::new(Real::with_config(output_cfg.real_cfg))
+        } else if common_cfg.synchronous {
+            Box::new(SynchronousSimulator::with_config(
+                output_cfg.simulator_cfg,
+                common_cfg,
+                clock,
+                sync_vision,
+                recorder,
+            ))
         } else {
             Box::new(Simulator::with_config(output_cfg.simulator_cfg, common_cfg))
         };
 
-        OutputPipeline { command_task }
+        OutputPipeline {
+            command_task,
+            recorder: recorder.clone(),
+        }
     }
 }
 
@@ -48,7 +75,16 @@ impl Component for OutputPipeline {
 }
 
 impl OutputComponent for OutputPipeline {
-    fn step(&mut self, commands: CommandMap, _tool_commands: ToolCommands) -> FeedbackMap {
-        self.command_task.step(commands)
+    fn step(&mut self, commands: CommandMap, tool_commands: ToolCommands) -> FeedbackMap {
+        for command in &tool_commands {
+            if let ToolCommand::Simulator(simulator_command) = command {
+                self.command_task.control_simulator(simulator_command);
+            }
+        }
+        // The log is flushed by the input pipeline at the next step.
+        self.recorder.record_commands(&commands);
+        let feedback = self.command_task.step(commands);
+        self.recorder.record_feedback(&feedback);
+        feedback
     }
 }
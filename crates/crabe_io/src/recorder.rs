@@ -0,0 +1,369 @@
+use crate::league::real::prepare_packet;
+use chrono::{DateTime, TimeZone, Utc};
+use crabe_framework::clock::SharedClock;
+use crabe_framework::data::output::{CommandMap, FeedbackMap};
+use crabe_protocol::protobuf::robot_packet::{BaseFeedback, BaseToPc};
+use log::{error, info};
+use prost::Message;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// The header at the start of the SSL log files.
+pub const LOG_HEADER: &[u8; 12] = b"SSL_LOG_FILE";
+/// The version of the SSL log format written.
+pub const LOG_VERSION: i32 = 1;
+
+/// The `MessageType` enum is the type of a message of an SSL log file. The types from 1000 are
+/// ours, the log players of the league skip them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum MessageType {
+    Blank = 0,
+    Unknown = 1,
+    Vision2010 = 2,
+    Refbox2013 = 3,
+    Vision2014 = 4,
+    VisionTracker2020 = 5,
+    Index2021 = 6,
+    /// The commands sent to our robots, as a `PcToBase` packet.
+    Commands = 1000,
+    /// The feedback of our robots, as a `BaseToPc` packet.
+    Feedback = 1001,
+}
+
+impl From<i32> for MessageType {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Blank,
+            2 => Self::Vision2010,
+            3 => Self::Refbox2013,
+            4 => Self::Vision2014,
+            5 => Self::VisionTracker2020,
+            6 => Self::Index2021,
+            1000 => Self::Commands,
+            1001 => Self::Feedback,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A message of an SSL log file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogMessage {
+    /// The time at which the message was received.
+    pub timestamp: DateTime<Utc>,
+    pub kind: MessageType,
+    /// The encoded protobuf packet.
+    pub payload: Vec<u8>,
+}
+
+/// The `LogWriter` struct writes the standard SSL log format: the `SSL_LOG_FILE` header and its
+/// version, then each message as its receive time in nanoseconds (`i64`), its type (`i32`), its
+/// size (`i32`) and its payload, all in big endian.
+pub struct LogWriter<W: Write> {
+    writer: W,
+}
+
+impl LogWriter<BufWriter<File>> {
+    /// Creates the log file `path`. When `path` is a directory (or ends with a `/`, the directory
+    /// being created), the file is created inside it and named after the current date, so that
+    /// each session leaves its own log.
+    pub fn create(path: &Path) -> Result<Self, Error> {
+        let path = if path.is_dir() || path.to_string_lossy().ends_with('/') {
+            std::fs::create_dir_all(path)?;
+            let name = Utc::now().format("crabe_%Y-%m-%d_%H-%M-%S.log").to_string();
+            path.join(name)
+        } else {
+            PathBuf::from(path)
+        };
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> LogWriter<W> {
+    /// Writes the header of the log into `writer`.
+    pub fn new(mut writer: W) -> Result<Self, Error> {
+        writer.write_all(LOG_HEADER)?;
+        writer.write_all(&LOG_VERSION.to_be_bytes())?;
+        Ok(Self { writer })
+    }
+
+    /// Writes a message of type `kind` received at `timestamp`.
+    pub fn write(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        kind: MessageType,
+        payload: &[u8],
+    ) -> Result<(), Error> {
+        let nanos = timestamp.timestamp_nanos_opt().unwrap_or_default();
+        self.writer.write_all(&nanos.to_be_bytes())?;
+        self.writer.write_all(&(kind as i32).to_be_bytes())?;
+        self.writer
+            .write_all(&(payload.len() as i32).to_be_bytes())?;
+        self.writer.write_all(payload)
+    }
+
+    /// Writes the commands sent to our robots.
+    pub fn write_commands(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        commands: &CommandMap,
+    ) -> Result<(), Error> {
+        if commands.is_empty() {
+            return Ok(());
+        }
+        let packet = prepare_packet(commands.iter().map(|(id, command)| (*id, *command)));
+        self.write(timestamp, MessageType::Commands, &packet.encode_to_vec())
+    }
+
+    /// Writes the feedback of our robots, the values they don't send being written as zero.
+    pub fn write_feedback(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        feedback: &FeedbackMap,
+    ) -> Result<(), Error> {
+        if feedback.is_empty() {
+            return Ok(());
+        }
+        let feedbacks = feedback.iter().map(|(id, feedback)| {
+            let [motor_1_speed, motor_2_speed, motor_3_speed, motor_4_speed] =
+                feedback.motor_speeds.unwrap_or_default();
+            BaseFeedback {
+                robot_id: *id,
+                motor_1_speed,
+                motor_2_speed,
+                motor_3_speed,
+                motor_4_speed,
+                voltage: feedback.voltage.unwrap_or_default(),
+                ir: feedback.has_ball,
+            }
+        });
+        let packet = BaseToPc {
+            feedbacks: feedbacks.collect(),
+        };
+        self.write(timestamp, MessageType::Feedback, &packet.encode_to_vec())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()
+    }
+}
+
+/// The `LogReader` struct reads the messages of an SSL log file, to replay it.
+pub struct LogReader<R: Read> {
+    reader: R,
+}
+
+impl LogReader<BufReader<File>> {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> LogReader<R> {
+    /// Checks the header of the log read from `reader`.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        if header != *LOG_HEADER {
+            return Err(Error::new(ErrorKind::InvalidData, "not an SSL log file"));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = i32::from_be_bytes(version);
+        if version != LOG_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported SSL log version {}", version),
+            ));
+        }
+        Ok(Self { reader })
+    }
+
+    fn read_message(&mut self) -> Result<Option<LogMessage>, Error> {
+        let mut timestamp = [0u8; 8];
+        match self.reader.read_exact(&mut timestamp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let mut kind = [0u8; 4];
+        self.reader.read_exact(&mut kind)?;
+        let mut size = [0u8; 4];
+        self.reader.read_exact(&mut size)?;
+        let size = usize::try_from(i32::from_be_bytes(size))
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "negative message size"))?;
+        let mut payload = vec![0u8; size];
+        self.reader.read_exact(&mut payload)?;
+
+        Ok(Some(LogMessage {
+            timestamp: Utc.timestamp_nanos(i64::from_be_bytes(timestamp)),
+            kind: i32::from_be_bytes(kind).into(),
+            payload,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for LogReader<R> {
+    type Item = Result<LogMessage, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_message().transpose()
+    }
+}
+
+/// The `Recorder` struct is a handle on the log file shared by the receiver threads and the
+/// pipelines, which record the packets as they receive or send them. Each message is stamped
+/// when it is written, so that the log holds the messages in their order of arrival.
+///
+/// Without a log file, the recorder records nothing.
+#[derive(Clone)]
+pub struct Recorder {
+    writer: Option<Arc<Mutex<LogWriter<BufWriter<File>>>>>,
+    clock: SharedClock,
+}
+
+impl Recorder {
+    /// Records to the log file `path` (see `LogWriter::create`), or nothing without a path.
+    pub fn with_config(path: Option<&Path>, clock: &SharedClock) -> Result<Self, Error> {
+        let writer = match path {
+            Some(path) => {
+                info!("Recording to {}", path.display());
+                Some(Arc::new(Mutex::new(LogWriter::create(path)?)))
+            }
+            None => None,
+        };
+        Ok(Self {
+            writer,
+            clock: clock.clone(),
+        })
+    }
+
+    fn with_writer(
+        &self,
+        write: impl FnOnce(&mut LogWriter<BufWriter<File>>, DateTime<Utc>) -> Result<(), Error>,
+    ) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let mut writer = writer.lock().unwrap();
+        // The time is read once the file is locked, so that the timestamps follow the order of
+        // the messages.
+        if let Err(e) = write(&mut writer, self.clock.now()) {
+            error!("Failed to write the log file: {}", e);
+        }
+    }
+
+    /// Records the packet `packet` of type `kind`, received now.
+    pub fn record(&self, kind: MessageType, packet: &impl Message) {
+        self.with_writer(|writer, now| writer.write(now, kind, &packet.encode_to_vec()));
+    }
+
+    /// Records the commands sent now to our robots.
+    pub fn record_commands(&self, commands: &CommandMap) {
+        self.with_writer(|writer, now| writer.write_commands(now, commands));
+    }
+
+    /// Records the feedback received now from our robots.
+    pub fn record_feedback(&self, feedback: &FeedbackMap) {
+        self.with_writer(|writer, now| writer.write_feedback(now, feedback));
+    }
+
+    /// Writes the recorded messages to the log file.
+    pub fn flush(&self) {
+        self.with_writer(|writer, _| writer.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use crabe_framework::clock::ManualClock;
+    use crabe_framework::data::output::{Command, Feedback};
+    use crabe_protocol::protobuf::robot_packet::PcToBase;
+    use crabe_protocol::protobuf::vision_packet::SslWrapperPacket;
+
+    #[test]
+    fn messages_are_read_back() {
+        let timestamp = Utc.timestamp_nanos(1_700_000_000_123_456_789);
+        let mut feedback = FeedbackMap::new();
+        feedback.insert(
+            2,
+            Feedback {
+                has_ball: true,
+                ..Default::default()
+            },
+        );
+        let mut commands = CommandMap::new();
+        commands.insert(
+            5,
+            Command {
+                forward_velocity: 1.5,
+                ..Default::default()
+            },
+        );
+
+        let mut writer = LogWriter::new(vec![]).unwrap();
+        let vision = SslWrapperPacket::default().encode_to_vec();
+        writer
+            .write(timestamp, MessageType::Vision2014, &vision)
+            .unwrap();
+        writer.write_feedback(timestamp, &feedback).unwrap();
+        writer.write_commands(timestamp, &commands).unwrap();
+        assert_eq!(&writer.writer[..12], LOG_HEADER);
+
+        let messages: Vec<_> = LogReader::new(writer.writer.as_slice())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let kinds: Vec<_> = messages.iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                MessageType::Vision2014,
+                MessageType::Feedback,
+                MessageType::Commands
+            ]
+        );
+        assert_eq!(messages[0].timestamp, timestamp);
+        let feedback = BaseToPc::decode(messages[1].payload.as_slice()).unwrap();
+        assert!(feedback.feedbacks[0].ir);
+        let packet = PcToBase::decode(messages[2].payload.as_slice()).unwrap();
+        assert_eq!(packet.commands[0].robot_id, 5);
+        assert_eq!(packet.commands[0].normal_velocity, 1.5);
+    }
+
+    #[test]
+    fn recorded_messages_are_stamped_in_order() {
+        let start = Utc.timestamp_nanos(1_700_000_000_000_000_000);
+        let clock = Arc::new(ManualClock::new(start));
+        let shared: SharedClock = clock.clone();
+        let path = std::env::temp_dir().join(format!("crabe_recorder_{}.log", std::process::id()));
+        let recorder = Recorder::with_config(Some(&path), &shared).unwrap();
+
+        recorder.record(MessageType::Vision2014, &SslWrapperPacket::default());
+        clock.advance(std::time::Duration::from_millis(5));
+        recorder.record_commands(&CommandMap::from([(1, Command::default())]));
+        recorder.flush();
+        Recorder::with_config(None, &shared)
+            .unwrap()
+            .record(MessageType::Vision2014, &SslWrapperPacket::default());
+
+        let messages: Vec<_> = LogReader::open(&path)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let stamped: Vec<_> = messages.iter().map(|m| (m.kind, m.timestamp)).collect();
+        assert_eq!(
+            stamped,
+            [
+                (MessageType::Vision2014, start),
+                (MessageType::Commands, start + Duration::milliseconds(5))
+            ]
+        );
+    }
+}
@@ -2,4 +2,4 @@ mod config;
 mod server;
 
 pub use config::ToolConfig;
-pub use server::ToolServer;
+pub use server::{ComponentNames, ToolServer};
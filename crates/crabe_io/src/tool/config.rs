@@ -1,7 +1,12 @@
 use clap::Args;
+use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct ToolConfig {
     #[arg(long, default_value_t = 10400)]
     pub tool_port: u16,
+    /// File from which the runtime parameters are loaded at startup, if it exists, and where the
+    /// tools save them.
+    #[arg(long, default_value = "parameters.json")]
+    pub parameters_file: PathBuf,
 }
@@ -1,42 +1,240 @@
-use crate::communication::WebSocketTransceiver;
+use crate::communication::{ClientId, WebSocketTransceiver};
 use crate::tool::config::ToolConfig;
+use crabe_framework::clock::SharedClock;
 use crabe_framework::component::{Component, ToolComponent};
 use crabe_framework::config::CommonConfig;
 use crabe_framework::data::output::CommandMap;
-use crabe_framework::data::tool::{ToolCommands, ToolData};
+use crabe_framework::data::tool::{ToolCommand, ToolCommands, ToolData};
 use crabe_framework::data::world::World;
+use crabe_framework::parameter::{parameters, ParameterInfo};
+use log::{error, warn};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::net::{Ipv4Addr, SocketAddrV4};
+use std::path::PathBuf;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ToolMessage {
     world: World,
+    #[serde(flatten)]
     data: ToolData,
 }
 
+/// The field viewer served to the browsers, which draws the `ToolMessage` stream.
+const VIEWER_PAGE: &str = include_str!("viewer.html");
+
+/// The requests of the tools.
 #[serde_as]
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase", tag = "requestType", content = "payload")]
 enum ToolRequest {
+    /// Commands the robots directly, on top of the decision.
     Commands(#[serde_as(as = "Vec<(_, _)>")] CommandMap),
+    /// Drives the pipeline: manager, strategies, halt, simulator, ...
+    ToolCommand(ToolCommand),
+    /// Lists the runtime parameters.
+    ListParameters,
+    /// Reads the runtime parameter with the given name.
+    GetParameter(String),
+    /// Changes the value of a runtime parameter.
+    SetParameter {
+        name: String,
+        value: serde_json::Value,
+    },
+    /// Saves the runtime parameters in the parameters file.
+    SaveParameters,
+}
+
+/// A request with the identifier chosen by the tool to match the reply, e.g.
+/// `{"id": 4, "requestType": "toolCommand", "payload": {"kind": "haltAll"}}`.
+#[derive(Debug, Deserialize)]
+struct ToolRequestEnvelope {
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(flatten)]
+    request: ToolRequest,
+}
+
+/// The reply to a request.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "responseType", content = "payload")]
+enum ToolResponse {
+    /// The request is accepted, it takes effect during the current iteration.
+    Ack,
+    /// The request is rejected, with the reason.
+    Error(String),
+    /// The runtime parameters, in reply to `listParameters`.
+    Parameters(Vec<ParameterInfo>),
+    /// A runtime parameter, in reply to `getParameter` and `setParameter`.
+    Parameter(ParameterInfo),
+}
+
+/// A reply, sent only to the tool that made the request.
+#[derive(Debug, Serialize)]
+struct ToolReply {
+    /// The identifier of the request, if it had one.
+    id: Option<u64>,
+    #[serde(flatten)]
+    response: ToolResponse,
+}
+
+/// The `ComponentNames` struct lists the managers, strategies and guards that the tools can
+/// name in their commands, as registered in the pipelines.
+#[derive(Clone, Debug, Default)]
+pub struct ComponentNames {
+    pub managers: Vec<&'static str>,
+    pub strategies: Vec<&'static str>,
+    pub guards: Vec<&'static str>,
+}
+
+impl ComponentNames {
+    /// Returns an error when `name` is not one of the `names` of the components of `kind`.
+    fn check(kind: &str, name: &str, names: &[&str]) -> Result<(), String> {
+        if names.contains(&name) {
+            Ok(())
+        } else {
+            Err(format!(
+                "unknown {} `{}` (available: {})",
+                kind,
+                name,
+                names.join(", ")
+            ))
+        }
+    }
+
+    /// Checks a strategy written as `name` or `name:robot_id`.
+    fn check_strategy(&self, strategy: &str) -> Result<(), String> {
+        let name = match strategy.split_once(':') {
+            Some((name, id)) => {
+                id.parse::<u8>()
+                    .map_err(|_| format!("invalid robot id `{}`", id))?;
+                name
+            }
+            None => strategy,
+        };
+        Self::check("strategy", name, &self.strategies)
+    }
+
+    /// Checks the managers, strategies and guards named by `command`, so that the tool is told
+    /// about a mistake instead of having its command ignored by the pipeline.
+    fn check_command(&self, command: &ToolCommand) -> Result<(), String> {
+        match command {
+            ToolCommand::SwitchManager {
+                manager,
+                strategies,
+            } => {
+                Self::check("manager", manager, &self.managers)?;
+                strategies.iter().try_for_each(|s| self.check_strategy(s))
+            }
+            ToolCommand::SetStrategies(strategies) => {
+                strategies.iter().try_for_each(|s| self.check_strategy(s))
+            }
+            ToolCommand::StartStrategy { name, .. } | ToolCommand::StopStrategy { name, .. } => {
+                Self::check("strategy", name, &self.strategies)
+            }
+            ToolCommand::EnableGuard(name) | ToolCommand::DisableGuard(name) => {
+                Self::check("guard", name, &self.guards)
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 pub struct ToolServer {
-    websocket: WebSocketTransceiver<ToolRequest, ToolMessage>,
+    websocket: WebSocketTransceiver<ToolRequestEnvelope, ToolMessage>,
+    /// Whether the robots are real, in which case the simulator can't be controlled.
+    real: bool,
+    parameters_file: PathBuf,
+    /// The components that the tool commands may name.
+    names: ComponentNames,
 }
 
 impl ToolServer {
-    pub fn with_config(tool_config: ToolConfig, _common_config: &CommonConfig) -> Self {
+    pub fn with_config(
+        tool_config: ToolConfig,
+        common_config: &CommonConfig,
+        _clock: &SharedClock,
+        names: ComponentNames,
+    ) -> Self {
+        if tool_config.parameters_file.exists() {
+            parameters()
+                .load(&tool_config.parameters_file)
+                .unwrap_or_else(|e| error!("Cannot load the parameters: {}", e));
+        }
+
         Self {
+            real: common_config.real,
+            parameters_file: tool_config.parameters_file,
+            names,
             websocket: WebSocketTransceiver::spawn(
                 SocketAddrV4::new(Ipv4Addr::LOCALHOST, tool_config.tool_port).into(),
+                Some(VIEWER_PAGE),
+                // The plot samples are only sent once, a slow tool must not miss them.
+                &["plots"],
             ),
         }
     }
 }
 
+impl ToolServer {
+    /// Handles a request, returning its reply.
+    fn handle(
+        &mut self,
+        request: ToolRequest,
+        world_data: &World,
+        commands: &mut CommandMap,
+        tool_commands: &mut ToolCommands,
+    ) -> ToolResponse {
+        match request {
+            ToolRequest::Commands(tool_commands) => commands.extend(tool_commands),
+            ToolRequest::ToolCommand(ToolCommand::Simulator(_)) if self.real => {
+                return ToolResponse::Error(
+                    "the simulator cannot be controlled with the real robots".to_string(),
+                );
+            }
+            // The tools work in the `World` frame, the simulator in the raw one.
+            ToolRequest::ToolCommand(ToolCommand::Simulator(command)) => {
+                tool_commands.push(ToolCommand::Simulator(command.mirror(&world_data.data)));
+            }
+            ToolRequest::ToolCommand(command) => match self.names.check_command(&command) {
+                Ok(()) => tool_commands.push(command),
+                Err(e) => return ToolResponse::Error(e),
+            },
+            ToolRequest::ListParameters => return ToolResponse::Parameters(parameters().list()),
+            ToolRequest::GetParameter(name) => {
+                return match parameters().get(&name) {
+                    Some(parameter) => ToolResponse::Parameter(parameter),
+                    None => ToolResponse::Error(format!("unknown parameter `{}`", name)),
+                };
+            }
+            ToolRequest::SetParameter { name, value } => {
+                let result = value
+                    .as_f64()
+                    .ok_or_else(|| format!("the value of `{}` must be a number", name))
+                    .and_then(|value| parameters().set(&name, value));
+                return match result {
+                    Ok(parameter) => ToolResponse::Parameter(parameter),
+                    Err(e) => ToolResponse::Error(e),
+                };
+            }
+            ToolRequest::SaveParameters => {
+                if let Err(e) = parameters().save(&self.parameters_file) {
+                    return ToolResponse::Error(e);
+                }
+            }
+        }
+        ToolResponse::Ack
+    }
+
+    fn reply(&self, client: ClientId, id: Option<u64>, response: ToolResponse) {
+        if let ToolResponse::Error(e) = &response {
+            warn!("Request {:?} of the tool {} rejected: {}", id, client, e);
+        }
+        self.websocket.reply(client, &ToolReply { id, response });
+    }
+}
+
 impl Component for ToolServer {
     fn close(self) {
         self.websocket.close();
@@ -50,19 +248,94 @@ impl ToolComponent for ToolServer {
         tool_data: &mut ToolData,
         commands: &mut CommandMap,
     ) -> ToolCommands {
-        let msg = ToolMessage {
-            data: tool_data.clone(),
-            world: world_data.clone(),
-        };
-        self.websocket.send(msg);
-        if let Some(request) = self.websocket.receive() {
-            println!("request");
+        // Nothing is cloned when no tool listens.
+        if self.websocket.has_clients() {
+            let msg = ToolMessage {
+                data: tool_data.clone(),
+                world: world_data.clone(),
+            };
+            self.websocket.send(msg);
+        }
+        let mut tool_commands = ToolCommands::default();
+        // The strategies control the simulator in the `World` frame too.
+        for command in tool_data.take_simulator_commands() {
+            if !self.real {
+                tool_commands.push(ToolCommand::Simulator(command.mirror(&world_data.data)));
+            }
+        }
+        while let Some((client, request)) = self.websocket.receive() {
             match request {
-                ToolRequest::Commands(tool_commands) => {
-                    commands.extend(tool_commands);
+                Ok(ToolRequestEnvelope { id, request }) => {
+                    let response = self.handle(request, world_data, commands, &mut tool_commands);
+                    self.reply(client, id, response);
                 }
+                Err(e) => self.reply(client, None, ToolResponse::Error(e)),
             }
         }
-        ToolCommands {}
+        tool_commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn requests_are_parsed_with_their_id() {
+        let envelope: ToolRequestEnvelope = serde_json::from_value(json!({
+            "id": 4,
+            "requestType": "toolCommand",
+            "payload": {"kind": "startStrategy", "payload": {"name": "square", "robotIds": [1, 2]}}
+        }))
+        .unwrap();
+        assert_eq!(envelope.id, Some(4));
+        assert!(matches!(
+            envelope.request,
+            ToolRequest::ToolCommand(ToolCommand::StartStrategy { ref robot_ids, .. })
+                if robot_ids == &vec![1, 2]
+        ));
+
+        let envelope: ToolRequestEnvelope =
+            serde_json::from_value(json!({"requestType": "commands", "payload": []})).unwrap();
+        assert_eq!(envelope.id, None);
+
+        let reply = ToolReply {
+            id: Some(4),
+            response: ToolResponse::Error("nope".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(reply).unwrap(),
+            json!({"id": 4, "responseType": "error", "payload": "nope"})
+        );
+    }
+
+    #[test]
+    fn commands_naming_unknown_components_are_rejected() {
+        let names = ComponentNames {
+            managers: vec!["manual"],
+            strategies: vec!["square"],
+            guards: vec!["speed"],
+        };
+        let check = |command: ToolCommand| names.check_command(&command);
+        let switch = |manager: &str, strategy: &str| ToolCommand::SwitchManager {
+            manager: manager.to_string(),
+            strategies: vec![strategy.to_string()],
+        };
+        assert_eq!(check(switch("manual", "square:1")), Ok(()));
+        assert_eq!(
+            check(switch("x", "square")),
+            Err("unknown manager `x` (available: manual)".to_string())
+        );
+        assert!(check(switch("manual", "x:1")).is_err());
+        assert!(check(switch("manual", "square:x")).is_err());
+        assert!(check(ToolCommand::SetStrategies(vec!["x".to_string()])).is_err());
+        assert!(check(ToolCommand::StartStrategy {
+            name: "x".to_string(),
+            robot_ids: vec![1],
+        })
+        .is_err());
+        assert_eq!(check(ToolCommand::EnableGuard("speed".to_string())), Ok(()));
+        assert!(check(ToolCommand::EnableGuard("x".to_string())).is_err());
     }
 }
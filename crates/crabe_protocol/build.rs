@@ -19,6 +19,7 @@ fn main() {
             "protobuf/simulation/ssl_simulation_control.proto",
             "protobuf/simulation/ssl_simulation_robot_control.proto",
             "protobuf/simulation/ssl_simulation_robot_feedback.proto",
+            "protobuf/simulation/ssl_simulation_synchronous.proto",
         ],
         &["protobuf/simulation/"],
     );
@@ -29,6 +30,12 @@ fn main() {
         &["protobuf/vision"],
     );
 
+    compile_packet(
+        "tracked_vision_packet",
+        &["protobuf/vision/messages_robocup_ssl_wrapper_tracked.proto"],
+        &["protobuf/vision"],
+    );
+
     compile_packet(
         "game_controller_packet",
         &["protobuf/game_controller/ssl_gc_referee_message.proto"],
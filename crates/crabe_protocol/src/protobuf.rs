@@ -1,4 +1,8 @@
 pub mod game_controller_packet;
 pub mod robot_packet;
+// Generated from the upstream .proto files, whose comments trip rustdoc lints.
+#[allow(clippy::doc_lazy_continuation)]
 pub mod simulation_packet;
+pub mod tracked_vision_packet;
+#[allow(clippy::doc_lazy_continuation)]
 pub mod vision_packet;
@@ -640,3 +640,84 @@ pub struct RobotControlResponse {
     #[prost(message, repeated, tag = "2")]
     pub feedback: ::prost::alloc::vec::Vec<RobotFeedback>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SslDetectionBall {
+    #[prost(float, required, tag = "1")]
+    pub confidence: f32,
+    #[prost(uint32, optional, tag = "2")]
+    pub area: ::core::option::Option<u32>,
+    #[prost(float, required, tag = "3")]
+    pub x: f32,
+    #[prost(float, required, tag = "4")]
+    pub y: f32,
+    #[prost(float, optional, tag = "5")]
+    pub z: ::core::option::Option<f32>,
+    #[prost(float, required, tag = "6")]
+    pub pixel_x: f32,
+    #[prost(float, required, tag = "7")]
+    pub pixel_y: f32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SslDetectionRobot {
+    #[prost(float, required, tag = "1")]
+    pub confidence: f32,
+    #[prost(uint32, optional, tag = "2")]
+    pub robot_id: ::core::option::Option<u32>,
+    #[prost(float, required, tag = "3")]
+    pub x: f32,
+    #[prost(float, required, tag = "4")]
+    pub y: f32,
+    #[prost(float, optional, tag = "5")]
+    pub orientation: ::core::option::Option<f32>,
+    #[prost(float, required, tag = "6")]
+    pub pixel_x: f32,
+    #[prost(float, required, tag = "7")]
+    pub pixel_y: f32,
+    #[prost(float, optional, tag = "8")]
+    pub height: ::core::option::Option<f32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SslDetectionFrame {
+    #[prost(uint32, required, tag = "1")]
+    pub frame_number: u32,
+    #[prost(double, required, tag = "2")]
+    pub t_capture: f64,
+    #[prost(double, required, tag = "3")]
+    pub t_sent: f64,
+    #[prost(uint32, required, tag = "4")]
+    pub camera_id: u32,
+    #[prost(message, repeated, tag = "5")]
+    pub balls: ::prost::alloc::vec::Vec<SslDetectionBall>,
+    #[prost(message, repeated, tag = "6")]
+    pub robots_yellow: ::prost::alloc::vec::Vec<SslDetectionRobot>,
+    #[prost(message, repeated, tag = "7")]
+    pub robots_blue: ::prost::alloc::vec::Vec<SslDetectionRobot>,
+}
+/// Request from the team to the simulator
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SimulationSyncRequest {
+    /// The simulation step \[s\] to perform
+    #[prost(float, optional, tag = "1")]
+    pub sim_step: ::core::option::Option<f32>,
+    /// An optional simulator command
+    #[prost(message, optional, tag = "2")]
+    pub simulator_command: ::core::option::Option<SimulatorCommand>,
+    /// An optional robot control command
+    #[prost(message, optional, tag = "3")]
+    pub robot_control: ::core::option::Option<RobotControl>,
+}
+/// Response to last SimulationSyncRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SimulationSyncResponse {
+    /// List of detection frames for all cameras with the state after the simulation step in the request was performed
+    #[prost(message, repeated, tag = "1")]
+    pub detection: ::prost::alloc::vec::Vec<SslDetectionFrame>,
+    /// An optional robot control response
+    #[prost(message, optional, tag = "2")]
+    pub robot_control_response: ::core::option::Option<RobotControlResponse>,
+}